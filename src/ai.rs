@@ -0,0 +1,631 @@
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+
+use crate::board::Board;
+use crate::direction::Direction;
+use crate::field::Field;
+use crate::field_gameover::available_moves;
+use crate::field_move_and_merge::move_and_merge_traced;
+use crate::game::{ExternalScore, Game, GameRng};
+use crate::game_actions::move_field;
+use crate::rules::Rules;
+use crate::score_calculator::calculate_points;
+use crate::tile_spawner::WeightedSpawner;
+
+/// The number of moves [`Game::hint`] looks ahead.
+pub const HINT_DEPTH: usize = 2;
+
+/// The value of a board without any move left, far below anything the
+/// [`WeightedHeuristic`] gives.
+const LOST_VALUE: f64 = -1e6;
+
+/// Spawns less likely than this are not searched any further but evaluated
+/// right away.
+const MIN_PROBABILITY: f64 = 1e-4;
+
+/// The default for [`MonteCarloPlayer::with_max_moves`], far more than a
+/// random or greedy playout of a classic game needs.
+const MAX_PLAYOUT_MOVES: usize = 10_000;
+
+/// Rates a board, higher is better. Implemented for closures, e.g.
+/// `|board: &Board| empty_cells(board)`.
+pub trait Heuristic<const W: usize = 4, const H: usize = W> {
+    fn evaluate(&self, board: &Board<W, H>) -> f64;
+}
+
+impl<F, const W: usize, const H: usize> Heuristic<W, H> for F
+where
+    F: Fn(&Board<W, H>) -> f64,
+{
+    fn evaluate(&self, board: &Board<W, H>) -> f64 {
+        self(board)
+    }
+}
+
+/// The weighted sum of [`empty_cells`], [`monotonicity`], [`smoothness`]
+/// and [`corner`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightedHeuristic {
+    empty_cells: f64,
+    monotonicity: f64,
+    smoothness: f64,
+    corner: f64,
+}
+
+impl WeightedHeuristic {
+    pub fn with_empty_cells(mut self, weight: f64) -> Self {
+        self.empty_cells = weight;
+        self
+    }
+
+    pub fn with_monotonicity(mut self, weight: f64) -> Self {
+        self.monotonicity = weight;
+        self
+    }
+
+    pub fn with_smoothness(mut self, weight: f64) -> Self {
+        self.smoothness = weight;
+        self
+    }
+
+    pub fn with_corner(mut self, weight: f64) -> Self {
+        self.corner = weight;
+        self
+    }
+}
+
+impl Default for WeightedHeuristic {
+    fn default() -> Self {
+        Self {
+            empty_cells: 2.7,
+            monotonicity: 1.0,
+            smoothness: 0.1,
+            corner: 1.0,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> Heuristic<W, H> for WeightedHeuristic {
+    fn evaluate(&self, board: &Board<W, H>) -> f64 {
+        self.empty_cells * empty_cells(board)
+            + self.monotonicity * monotonicity(board)
+            + self.smoothness * smoothness(board)
+            + self.corner * corner(board)
+    }
+}
+
+/// The number of empty cells.
+pub fn empty_cells<const W: usize, const H: usize>(board: &Board<W, H>) -> f64 {
+    board.empty_cells().len() as f64
+}
+
+/// `0` if the exponents only increase or only decrease along every row and
+/// column, and the more negative the more they go up and down. Empty cells
+/// are skipped.
+pub fn monotonicity<const W: usize, const H: usize>(board: &Board<W, H>) -> f64 {
+    let exponents = exponents(board);
+    let penalty = |line: &mut dyn Iterator<Item = u8>| {
+        let (mut increase, mut decrease) = (0u32, 0u32);
+        let mut previous = None;
+        for exponent in line.filter(|exponent| *exponent != 0) {
+            if let Some(previous) = previous {
+                if exponent > previous {
+                    increase += (exponent - previous) as u32;
+                } else {
+                    decrease += (previous - exponent) as u32;
+                }
+            }
+            previous = Some(exponent);
+        }
+        increase.min(decrease)
+    };
+    let rows: u32 = (0..H)
+        .map(|row| penalty(&mut (0..W).map(|column| exponents[row][column])))
+        .sum();
+    let columns: u32 = (0..W)
+        .map(|column| penalty(&mut (0..H).map(|row| exponents[row][column])))
+        .sum();
+    -((rows + columns) as f64)
+}
+
+/// Minus the summed exponent difference of all neighbouring tiles, so `0`
+/// if every tile only has equal tiles next to it.
+pub fn smoothness<const W: usize, const H: usize>(board: &Board<W, H>) -> f64 {
+    let exponents = exponents(board);
+    let mut difference = 0;
+    for row in 0..H {
+        for column in 0..W {
+            let exponent = exponents[row][column];
+            if exponent == 0 {
+                continue;
+            }
+            for (next_row, next_column) in [(row + 1, column), (row, column + 1)] {
+                let next = exponents
+                    .get(next_row)
+                    .and_then(|cells| cells.get(next_column));
+                if let Some(&next) = next.filter(|next| **next != 0) {
+                    difference += exponent.abs_diff(next) as u32;
+                }
+            }
+        }
+    }
+    -(difference as f64)
+}
+
+/// The exponent of the largest tile if it lies in a corner, otherwise `0`.
+pub fn corner<const W: usize, const H: usize>(board: &Board<W, H>) -> f64 {
+    let exponents = exponents(board);
+    let largest = exponents.iter().flatten().max().copied().unwrap_or(0);
+    let corners = [(0, 0), (0, W - 1), (H - 1, 0), (H - 1, W - 1)];
+    if corners
+        .iter()
+        .any(|&(row, column)| exponents[row][column] == largest)
+    {
+        largest as f64
+    } else {
+        0.0
+    }
+}
+
+fn exponents<const W: usize, const H: usize>(board: &Board<W, H>) -> [[u8; W]; H] {
+    board.field().map(|row| row.map(|cell| cell.exponent()))
+}
+
+/// A depth-limited expectimax search: the player picks the move with the
+/// best expected value, and the spawns follow the rules, see
+/// [`Rules::enumerate_spawns`]. The boards at the end of the search are
+/// rated by the heuristic.
+#[derive(Clone, Debug)]
+pub struct Expectimax<E = WeightedHeuristic> {
+    depth: usize,
+    heuristic: E,
+    rules: Rules,
+}
+
+impl Expectimax {
+    /// Searches `depth` moves ahead, at least one, with the classic rules and
+    /// the [`WeightedHeuristic`].
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            heuristic: WeightedHeuristic::default(),
+            rules: Rules::classic(),
+        }
+    }
+}
+
+impl<E> Expectimax<E> {
+    pub fn with_heuristic<F>(self, heuristic: F) -> Expectimax<F> {
+        Expectimax {
+            depth: self.depth,
+            heuristic,
+            rules: self.rules,
+        }
+    }
+
+    /// Searches with the spawns and merges of the rules.
+    pub fn with_rules(mut self, rules: &Rules) -> Self {
+        self.rules = rules.clone();
+        self
+    }
+
+    /// The move with the best expected value, `None` if no move is left.
+    /// The first of the moves in [`Direction::ALL`] wins a tie.
+    pub fn best_move<const W: usize, const H: usize>(&self, board: Board<W, H>) -> Option<Direction>
+    where
+        E: Heuristic<W, H>,
+    {
+        let mut best: Option<(Direction, f64)> = None;
+        for (direction, value) in self.evaluate_moves(board) {
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((direction, value));
+            }
+        }
+        best.map(|(direction, _)| direction)
+    }
+
+    /// The expected value of every move which changes the board.
+    pub fn evaluate_moves<const W: usize, const H: usize>(
+        &self,
+        board: Board<W, H>,
+    ) -> Vec<(Direction, f64)>
+    where
+        E: Heuristic<W, H>,
+    {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                let moved = self.rules.after_state(board, direction);
+                (moved != board).then(|| (direction, self.spawn_node(moved, self.depth - 1, 1.0)))
+            })
+            .collect()
+    }
+
+    fn move_node<const W: usize, const H: usize>(
+        &self,
+        board: Board<W, H>,
+        depth: usize,
+        probability: f64,
+    ) -> f64
+    where
+        E: Heuristic<W, H>,
+    {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                let moved = self.rules.after_state(board, direction);
+                (moved != board).then(|| self.spawn_node(moved, depth - 1, probability))
+            })
+            .fold(None, |best: Option<f64>, value| {
+                Some(best.map_or(value, |best| best.max(value)))
+            })
+            .unwrap_or(LOST_VALUE)
+    }
+
+    /// The expected value over all spawns, with `depth` moves left after
+    /// the spawns.
+    fn spawn_node<const W: usize, const H: usize>(
+        &self,
+        board: Board<W, H>,
+        depth: usize,
+        probability: f64,
+    ) -> f64
+    where
+        E: Heuristic<W, H>,
+    {
+        self.rules
+            .enumerate_spawns(board)
+            .map(|(spawned, spawn_probability)| {
+                let value = if depth == 0 || probability * spawn_probability < MIN_PROBABILITY {
+                    self.heuristic.evaluate(&spawned)
+                } else {
+                    self.move_node(spawned, depth, probability * spawn_probability)
+                };
+                spawn_probability * value
+            })
+            .sum()
+    }
+}
+
+impl<R: Rng + Clone, const W: usize, const H: usize> Game<R, W, H> {
+    /// The move an [`Expectimax`] search of [`HINT_DEPTH`] moves with the
+    /// rules of the game suggests, `None` if no move is left.
+    pub fn hint(&self) -> Option<Direction> {
+        Expectimax::new(HINT_DEPTH)
+            .with_rules(self.rules())
+            .best_move(self.get_board())
+    }
+}
+
+/// How a [`MonteCarloPlayer`] picks the moves of a playout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Playout {
+    /// Any move which changes the board.
+    #[default]
+    Random,
+    /// The move with the most points, a random one of them on a tie.
+    Greedy,
+}
+
+/// How long a [`MonteCarloPlayer`] searches for a single move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Budget {
+    /// The number of playouts for every possible move.
+    Playouts(usize),
+    /// Playouts for all possible moves in turn until the time is up, at least
+    /// one for each. The number of playouts depends on the machine, so the
+    /// chosen moves are not reproducible.
+    Time(Duration),
+}
+
+/// Rates every possible move by the average points which playouts starting
+/// with it score until the game is over.
+///
+/// With a [`Budget::Playouts`] the same seed always picks the same moves.
+#[derive(Clone, Debug)]
+pub struct MonteCarloPlayer<R = GameRng> {
+    rng: R,
+    budget: Budget,
+    playout: Playout,
+    max_moves: usize,
+    rules: Rules,
+    spawner: WeightedSpawner,
+}
+
+impl MonteCarloPlayer {
+    /// 100 random playouts per move with the classic rules.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(GameRng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> MonteCarloPlayer<R> {
+    pub fn with_rng(rng: R) -> Self {
+        let rules = Rules::classic();
+        Self {
+            rng,
+            budget: Budget::Playouts(100),
+            playout: Playout::Random,
+            max_moves: MAX_PLAYOUT_MOVES,
+            spawner: rules.spawner(),
+            rules,
+        }
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn with_playout(mut self, playout: Playout) -> Self {
+        self.playout = playout;
+        self
+    }
+
+    /// Ends every playout after this many moves, even if moves are left.
+    /// This ends playouts with rules which never fill the board, e.g.
+    /// without any spawns. 10000 by default.
+    pub fn with_max_moves(mut self, max_moves: usize) -> Self {
+        self.max_moves = max_moves;
+        self
+    }
+
+    /// Plays out with the spawns, merges and spawn counts of the rules.
+    pub fn with_rules(mut self, rules: &Rules) -> Self {
+        self.rules = rules.clone();
+        self.spawner = rules.spawner();
+        self
+    }
+
+    /// The move with the highest average points, `None` if no move is
+    /// left. The first of the moves in [`Direction::ALL`] wins a tie.
+    pub fn best_move<const W: usize, const H: usize>(
+        &mut self,
+        board: Board<W, H>,
+    ) -> Option<Direction> {
+        let mut best: Option<(Direction, f64)> = None;
+        for (direction, value) in self.evaluate_moves(board) {
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((direction, value));
+            }
+        }
+        best.map(|(direction, _)| direction)
+    }
+
+    /// The average points the playouts of every move which changes the board
+    /// score, counted from the given board on. The score reached before it
+    /// is not included.
+    pub fn evaluate_moves<const W: usize, const H: usize>(
+        &mut self,
+        board: Board<W, H>,
+    ) -> Vec<(Direction, f64)> {
+        let field = board.field();
+        let directions: Vec<Direction> = available_moves(field).collect();
+        let mut totals = vec![0; directions.len()];
+        let mut playouts = 0;
+        let start = Instant::now();
+        while !directions.is_empty() && self.has_budget_left(playouts, start) {
+            for (index, direction) in directions.iter().enumerate() {
+                totals[index] += self.play_out(field, *direction);
+            }
+            playouts += 1;
+        }
+        directions
+            .into_iter()
+            .zip(totals)
+            .map(|(direction, total)| (direction, total as f64 / playouts.max(1) as f64))
+            .collect()
+    }
+
+    fn has_budget_left(&self, playouts: usize, start: Instant) -> bool {
+        match self.budget {
+            Budget::Playouts(count) => playouts < count,
+            Budget::Time(duration) => playouts == 0 || start.elapsed() < duration,
+        }
+    }
+
+    /// Plays `first` and then until no move is left or the moves are used up,
+    /// returning the points.
+    fn play_out<const W: usize, const H: usize>(
+        &mut self,
+        field: Field<W, H>,
+        first: Direction,
+    ) -> ExternalScore {
+        let mut field = field;
+        let mut direction = Some(first);
+        let mut points = 0;
+        let mut moves = 0;
+        while let Some(next) = direction {
+            let (moved, outcome, _) =
+                move_field(field, next, &self.rules, &mut self.spawner, &mut self.rng);
+            field = moved;
+            points += outcome.points;
+            moves += 1;
+            if moves >= self.max_moves {
+                break;
+            }
+            direction = self.choose_move(field);
+        }
+        points
+    }
+
+    fn choose_move<const W: usize, const H: usize>(
+        &mut self,
+        field: Field<W, H>,
+    ) -> Option<Direction> {
+        let mut candidates: Vec<Direction> = available_moves(field).collect();
+        if self.playout == Playout::Greedy {
+            let points = |direction: &Direction| {
+                let (_, merges, _) =
+                    move_and_merge_traced(field, *direction, self.rules.chain_merges());
+                calculate_points(&merges)
+            };
+            let most = candidates.iter().map(points).max().unwrap_or(0);
+            candidates.retain(|direction| points(direction) == most);
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(candidates[self.rng.gen_range(0..candidates.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::*;
+    use crate::game::GameRng;
+    use rand::SeedableRng;
+
+    fn board(notation: &str) -> Board {
+        Board::from_notation(notation).unwrap()
+    }
+
+    #[test]
+    fn it_should_count_empty_cells() {
+        assert_eq!(empty_cells(&board("2.4./..../..../...2")), 13.0);
+    }
+
+    #[test]
+    fn it_should_not_penalize_monotonic_boards() {
+        assert_eq!(monotonicity(&board("16,8,4,2/8,4,2./4,2../2...")), 0.0);
+        assert_eq!(monotonicity(&board("2,8,4./..../..../....")), -1.0);
+    }
+
+    #[test]
+    fn it_should_sum_the_differences_of_neighbours() {
+        assert_eq!(smoothness(&board("2,2../..../..../....")), 0.0);
+        assert_eq!(smoothness(&board("2,8../4.../..../....")), -3.0);
+    }
+
+    #[test]
+    fn it_should_reward_the_largest_tile_in_a_corner() {
+        assert_eq!(corner(&board("...64/.2../..../....")), 6.0);
+        assert_eq!(corner(&board("..../.64../..../...2")), 0.0);
+    }
+
+    #[test]
+    fn it_should_take_the_only_possible_move() {
+        let board = board("2,4,8,16/4,8,16,32/8,16,32,64/16,32,64.");
+        let expectimax = Expectimax::new(2);
+        assert_eq!(expectimax.evaluate_moves(board).len(), 2);
+        assert!(matches!(
+            expectimax.best_move(board),
+            Some(Direction::Right) | Some(Direction::Down)
+        ));
+    }
+
+    #[test]
+    fn it_should_find_no_move_on_a_lost_board() {
+        let board = board("2,4,2,4/4,2,4,2/2,4,2,4/4,2,4,2");
+        assert_eq!(Expectimax::new(3).best_move(board), None);
+    }
+
+    #[test]
+    fn it_should_merge_towards_the_corner() {
+        let board = board("..../..../..../8,4,2,2");
+        assert_eq!(Expectimax::new(2).best_move(board), Some(Direction::Left));
+    }
+
+    #[test]
+    fn it_should_use_a_custom_heuristic() {
+        // Prefers boards with the tile in the top right corner
+        let heuristic = |board: &Board| board.get((0, 3)).unwrap_or(0) as f64;
+        let expectimax = Expectimax::new(1).with_heuristic(heuristic);
+        assert_eq!(
+            expectimax.best_move(board("..../..../..../...2")),
+            Some(Direction::Up)
+        );
+    }
+
+    #[test]
+    fn it_should_hint_a_possible_move() {
+        let game = Game::with_seed(4);
+        let hint = game.hint().unwrap();
+        assert!(game.can_move(hint));
+    }
+
+    #[test]
+    fn it_should_play_better_than_random_moves() {
+        let mut game = Game::with_seed(1);
+        let expectimax = Expectimax::new(1);
+        while !game.status().is_finished() {
+            let direction = expectimax.best_move(game.get_board()).unwrap();
+            game.make_move(direction).unwrap();
+        }
+        let mut random_game = Game::with_seed(1);
+        let mut rng = GameRng::seed_from_u64(1);
+        while !random_game.status().is_finished() {
+            let direction = Direction::ALL[rng.gen_range(0..4)];
+            random_game.make_move(direction).unwrap();
+        }
+        assert!(game.get_score() > random_game.get_score());
+    }
+
+    #[test]
+    fn it_should_reproduce_the_evaluation_with_the_same_seed() {
+        let board = board("2.4./..../..8./2...");
+        let evaluate = |seed| {
+            MonteCarloPlayer::with_seed(seed)
+                .with_budget(Budget::Playouts(10))
+                .evaluate_moves(board)
+        };
+        assert_eq!(evaluate(3), evaluate(3));
+        assert_ne!(evaluate(3), evaluate(4));
+        assert_eq!(evaluate(3).len(), 4);
+    }
+
+    #[test]
+    fn it_should_only_evaluate_possible_moves() {
+        let mut player = MonteCarloPlayer::with_seed(1).with_budget(Budget::Playouts(5));
+        let board = board("2,4,8,16/4,8,16,32/8,16,32,64/16,32,64.");
+        let directions: Vec<Direction> = player
+            .evaluate_moves(board)
+            .into_iter()
+            .map(|(direction, _)| direction)
+            .collect();
+        assert_eq!(directions, vec![Direction::Right, Direction::Down]);
+        let lost = self::board("2,4,2,4/4,2,4,2/2,4,2,4/4,2,4,2");
+        assert_eq!(player.best_move(lost), None);
+    }
+
+    #[test]
+    fn it_should_play_out_at_least_once_within_a_time_budget() {
+        let mut player = MonteCarloPlayer::with_seed(1).with_budget(Budget::Time(Duration::ZERO));
+        let moves = player.evaluate_moves(board("2,2../..../..../...."));
+        assert!(moves.iter().all(|(_, points)| *points >= 4.0));
+    }
+
+    #[test]
+    fn it_should_end_playouts_with_rules_which_never_fill_the_board() {
+        let rules = Rules::classic().with_spawns_per_move(0);
+        let mut player = MonteCarloPlayer::with_seed(1)
+            .with_budget(Budget::Playouts(2))
+            .with_rules(&rules)
+            .with_max_moves(50);
+        let moves = player.evaluate_moves(board("2,2../..../..../...."));
+        assert_eq!(moves.len(), 3);
+        // Only the first merge scores, nothing spawns afterwards
+        assert!(moves.iter().all(|(_, points)| *points <= 4.0));
+    }
+
+    #[test]
+    fn it_should_play_better_with_greedy_playouts_than_random_moves() {
+        // Games on a 3x3 board are short enough for many playouts
+        let mut player = MonteCarloPlayer::with_seed(1)
+            .with_budget(Budget::Playouts(3))
+            .with_playout(Playout::Greedy);
+        let mut game = Game::<GameRng, 3>::with_rng(GameRng::seed_from_u64(1));
+        while !game.status().is_finished() {
+            let direction = player.best_move(game.get_board()).unwrap();
+            game.make_move(direction).unwrap();
+        }
+        let mut random_game = Game::<GameRng, 3>::with_rng(GameRng::seed_from_u64(1));
+        let mut rng = GameRng::seed_from_u64(1);
+        while !random_game.status().is_finished() {
+            let direction = Direction::ALL[rng.gen_range(0..4)];
+            random_game.make_move(direction).unwrap();
+        }
+        assert!(game.get_score() > random_game.get_score());
+    }
+}
@@ -0,0 +1,414 @@
+//! Plays 2048 in the terminal:
+//!
+//! ```text
+//! cargo run --features tui --bin tui [SEED]
+//! ```
+//!
+//! Arrow keys, `wasd` or `hjkl` move, `u` undoes, `r` redoes, `c` continues
+//! after a win, `n` starts a new game, `e` starts a new game with an entered
+//! seed and `q` quits.
+
+use std::env;
+use std::io::{self, Write};
+use std::process;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use engine_2048_rs::direction::Direction;
+use engine_2048_rs::game::Game;
+use engine_2048_rs::game_status::GameStatus;
+use engine_2048_rs::history::HistoryDepth;
+use engine_2048_rs::render::tile_color;
+
+const SIZE: usize = 4;
+/// Wide enough for `131072`, the largest tile of a 4x4 game.
+const CELL_WIDTH: usize = 6;
+const GRID_TOP: u16 = 3;
+const GRID_WIDTH: usize = SIZE * (CELL_WIDTH + 3) + 1;
+const HELP_TOP: u16 = GRID_TOP + 2 * SIZE as u16 + 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Command {
+    Move(Direction),
+    Undo,
+    Redo,
+    Continue,
+    NewGame,
+    EnterSeed,
+    Quit,
+}
+
+/// Ctrl+C, which quits in any mode, as raw mode does not turn it into a
+/// signal.
+fn is_interrupt(key: KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c')
+}
+
+fn command(key: KeyEvent) -> Option<Command> {
+    if is_interrupt(key) {
+        return Some(Command::Quit);
+    }
+    let command = match key.code {
+        KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('k') => Command::Move(Direction::Up),
+        KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('j') => Command::Move(Direction::Down),
+        KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('h') => Command::Move(Direction::Left),
+        KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('l') => Command::Move(Direction::Right),
+        KeyCode::Char('u') => Command::Undo,
+        KeyCode::Char('r') => Command::Redo,
+        KeyCode::Char('c') => Command::Continue,
+        KeyCode::Char('n') => Command::NewGame,
+        KeyCode::Char('e') => Command::EnterSeed,
+        KeyCode::Char('q') | KeyCode::Esc => Command::Quit,
+        _ => return None,
+    };
+    Some(command)
+}
+
+struct App {
+    game: Game,
+    seed: u64,
+    best: u64,
+    /// The digits typed so far while entering a seed.
+    seed_input: Option<String>,
+    message: String,
+}
+
+impl App {
+    fn new(seed: u64) -> Self {
+        Self {
+            game: new_game(seed),
+            seed,
+            best: 0,
+            seed_input: None,
+            message: String::new(),
+        }
+    }
+
+    fn start(&mut self, seed: u64) {
+        self.game = new_game(seed);
+        self.seed = seed;
+        self.message = format!("new game with seed {}", seed);
+    }
+
+    /// Returns `false` once the player quits.
+    fn handle(&mut self, key: KeyEvent) -> bool {
+        if self.seed_input.is_some() {
+            return self.handle_seed_input(key);
+        }
+        let Some(command) = command(key) else {
+            return true;
+        };
+        self.message.clear();
+        let result = match command {
+            Command::Move(direction) => self.game.make_move(direction).map(|_| ()),
+            Command::Undo => self.game.undo(),
+            Command::Redo => self.game.redo(),
+            Command::Continue => self.game.continue_after_win(),
+            Command::NewGame => {
+                self.start(random_seed());
+                Ok(())
+            }
+            Command::EnterSeed => {
+                self.seed_input = Some(String::new());
+                Ok(())
+            }
+            Command::Quit => return false,
+        };
+        if let Err(error) = result {
+            self.message = error.to_string();
+        }
+        self.best = self.best.max(self.game.get_score());
+        true
+    }
+
+    /// Returns `false` once the player quits.
+    fn handle_seed_input(&mut self, key: KeyEvent) -> bool {
+        if is_interrupt(key) {
+            return false;
+        }
+        let Some(input) = self.seed_input.as_mut() else {
+            return true;
+        };
+        match key.code {
+            KeyCode::Char(digit @ '0'..='9') => input.push(digit),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.seed_input = None,
+            KeyCode::Enter => match input.parse() {
+                Ok(seed) => {
+                    self.seed_input = None;
+                    self.start(seed);
+                }
+                Err(_) => self.message = format!("{:?} is not a seed", input),
+            },
+            _ => {}
+        }
+        true
+    }
+}
+
+fn new_game(seed: u64) -> Game {
+    Game::with_seed(seed).with_history(HistoryDepth::Unlimited)
+}
+
+/// Small enough to type back in with `e`.
+fn random_seed() -> u64 {
+    rand::random::<u32>() as u64
+}
+
+/// Remembers what is on the terminal, so that a frame only redraws the cells
+/// which changed.
+#[derive(Default)]
+struct Screen {
+    cells: Option<[[u64; SIZE]; SIZE]>,
+    overlay: bool,
+}
+
+impl Screen {
+    fn invalidate(&mut self) {
+        self.cells = None;
+        self.overlay = false;
+    }
+
+    fn draw(&mut self, out: &mut impl Write, app: &App) -> io::Result<()> {
+        let finished = app.game.status().is_finished();
+        if self.overlay && !finished {
+            self.invalidate();
+        }
+        if self.cells.is_none() {
+            queue!(out, Clear(ClearType::All))?;
+            draw_frame(out)?;
+        }
+        let values = app.game.get_board().to_values();
+        for (row, row_values) in values.iter().enumerate() {
+            for (column, value) in row_values.iter().enumerate() {
+                let drawn = self.cells.map(|cells| cells[row][column]);
+                if drawn != Some(*value) {
+                    draw_cell(out, row, column, *value)?;
+                }
+            }
+        }
+        self.cells = Some(values);
+
+        queue!(
+            out,
+            MoveTo(0, 0),
+            SetAttribute(Attribute::Bold),
+            Print("2048"),
+            SetAttribute(Attribute::Reset),
+            Print(format!("  seed {}", app.seed)),
+            Clear(ClearType::UntilNewLine),
+            MoveTo(0, 1),
+            Print(format!(
+                "Score: {:<8} Best: {:<8} Moves: {}",
+                app.game.get_score(),
+                app.best,
+                app.game.get_move_count()
+            )),
+            Clear(ClearType::UntilNewLine),
+            MoveTo(0, HELP_TOP + 2),
+        )?;
+        match &app.seed_input {
+            Some(input) => queue!(out, Print(format!("Seed: {}_", input)))?,
+            None => queue!(out, Print(&app.message))?,
+        }
+        queue!(out, Clear(ClearType::UntilNewLine))?;
+
+        match app.game.status() {
+            GameStatus::Won => {
+                self.draw_overlay(out, "You win!", "c continue, n new game")?;
+            }
+            GameStatus::Lost => {
+                self.draw_overlay(out, "Game over!", "u undo, n new game")?;
+            }
+            GameStatus::Playing | GameStatus::ContinuePlaying => {}
+        }
+        out.flush()
+    }
+
+    /// Draws a box over the middle of the grid. The grid is drawn completely
+    /// again once the box is gone.
+    fn draw_overlay(&mut self, out: &mut impl Write, title: &str, hint: &str) -> io::Result<()> {
+        let inner = GRID_WIDTH - 4;
+        let top = GRID_TOP + SIZE as u16 - 2;
+        let lines = [
+            format!("╔{}╗", "═".repeat(inner)),
+            format!("║{:^inner$}║", title, inner = inner),
+            format!("║{:^inner$}║", hint, inner = inner),
+            format!("╚{}╝", "═".repeat(inner)),
+        ];
+        queue!(out, SetAttribute(Attribute::Bold))?;
+        for (index, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(2, top + index as u16), Print(line))?;
+        }
+        self.overlay = true;
+        queue!(out, SetAttribute(Attribute::Reset))
+    }
+}
+
+fn draw_frame(out: &mut impl Write) -> io::Result<()> {
+    let line = |left: &str, crossing: &str, right: &str| {
+        let cells = vec!["─".repeat(CELL_WIDTH + 2); SIZE].join(crossing);
+        format!("{}{}{}", left, cells, right)
+    };
+    let empty_row = format!(
+        "│{}",
+        format!("{}│", " ".repeat(CELL_WIDTH + 2)).repeat(SIZE)
+    );
+    queue!(out, MoveTo(0, GRID_TOP), Print(line("┌", "┬", "┐")))?;
+    for row in 0..SIZE as u16 {
+        let y = GRID_TOP + 1 + 2 * row;
+        queue!(out, MoveTo(0, y), Print(&empty_row))?;
+        let separator = if row + 1 == SIZE as u16 {
+            line("└", "┴", "┘")
+        } else {
+            line("├", "┼", "┤")
+        };
+        queue!(out, MoveTo(0, y + 1), Print(separator))?;
+    }
+    queue!(
+        out,
+        MoveTo(0, HELP_TOP),
+        Print("arrows/wasd/hjkl move  u undo  r redo  c continue"),
+        MoveTo(0, HELP_TOP + 1),
+        Print("n new game  e new game with seed  q quit"),
+    )
+}
+
+fn draw_cell(out: &mut impl Write, row: usize, column: usize, value: u64) -> io::Result<()> {
+    let x = 1 + column * (CELL_WIDTH + 3);
+    let y = GRID_TOP as usize + 1 + 2 * row;
+    queue!(out, MoveTo(x as u16 + 1, y as u16))?;
+    if value == 0 {
+        return queue!(out, Print(" ".repeat(CELL_WIDTH)));
+    }
+    let color = Color::AnsiValue(tile_color(value.trailing_zeros() as u8));
+    queue!(
+        out,
+        SetForegroundColor(color),
+        Print(format!("{:^width$}", value, width = CELL_WIDTH)),
+        ResetColor
+    )
+}
+
+fn run(out: &mut impl Write, app: &mut App) -> io::Result<()> {
+    let mut screen = Screen::default();
+    screen.draw(out, app)?;
+    loop {
+        match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                if !app.handle(key) {
+                    return Ok(());
+                }
+            }
+            Event::Resize(_, _) => screen.invalidate(),
+            _ => continue,
+        }
+        screen.draw(out, app)?;
+    }
+}
+
+fn main() {
+    let seed = match env::args().nth(1) {
+        None => random_seed(),
+        Some(seed) => seed.parse().unwrap_or_else(|_| {
+            eprintln!("usage: tui [SEED], {:?} is not a seed", seed);
+            process::exit(2);
+        }),
+    };
+    let mut app = App::new(seed);
+    let mut out = io::stdout();
+
+    let result = terminal::enable_raw_mode()
+        .and_then(|_| execute!(out, EnterAlternateScreen, Hide))
+        .and_then(|_| run(&mut out, &mut app));
+    // Restore the terminal even if drawing failed
+    let _ = execute!(out, Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+    println!(
+        "Score: {}, best: {}, seed: {}",
+        app.game.get_score(),
+        app.best,
+        app.seed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn it_should_move_with_arrows_wasd_and_vim_keys() {
+        for code in [KeyCode::Up, KeyCode::Char('w'), KeyCode::Char('k')] {
+            assert_eq!(command(key(code)), Some(Command::Move(Direction::Up)));
+        }
+        for code in [KeyCode::Left, KeyCode::Char('a'), KeyCode::Char('h')] {
+            assert_eq!(command(key(code)), Some(Command::Move(Direction::Left)));
+        }
+    }
+
+    #[test]
+    fn it_should_start_a_new_game_with_an_entered_seed() {
+        let mut app = App::new(1);
+        app.handle(key(KeyCode::Char('e')));
+        for digit in "42".chars() {
+            app.handle(key(KeyCode::Char(digit)));
+        }
+        app.handle(key(KeyCode::Enter));
+        assert_eq!(app.seed, 42);
+        assert_eq!(app.game.get_board(), Game::with_seed(42).get_board());
+    }
+
+    #[test]
+    fn it_should_quit_with_ctrl_c_while_entering_a_seed() {
+        let mut app = App::new(1);
+        app.handle(key(KeyCode::Char('e')));
+        assert!(app.handle(key(KeyCode::Char('c'))));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(!app.handle(ctrl_c));
+    }
+
+    #[test]
+    fn it_should_keep_the_best_score_across_games() {
+        let mut app = App::new(7);
+        for _ in 0..1000 {
+            if app.game.get_score() > 0 {
+                break;
+            }
+            for code in [KeyCode::Left, KeyCode::Up] {
+                app.handle(key(code));
+            }
+        }
+        let score = app.game.get_score();
+        assert!(score > 0);
+        app.handle(key(KeyCode::Char('n')));
+        assert_eq!(app.game.get_score(), 0);
+        assert_eq!(app.best, score);
+    }
+
+    #[test]
+    fn it_should_only_redraw_changed_cells() {
+        let app = App::new(3);
+        let mut screen = Screen::default();
+        let mut first = Vec::new();
+        screen.draw(&mut first, &app).unwrap();
+        let mut unchanged = Vec::new();
+        screen.draw(&mut unchanged, &app).unwrap();
+        assert!(unchanged.len() < first.len() / 4);
+        assert!(!String::from_utf8(unchanged).unwrap().contains('┌'));
+    }
+}
@@ -0,0 +1,386 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::board::Board;
+use crate::direction::Direction;
+use crate::score_calculator::Score;
+
+/// The largest exponent which fits into the four bits of a cell, `2^15`.
+const MAX_EXPONENT: u8 = 15;
+const ROW_MASK: u64 = 0xFFFF;
+const ROW_COUNT: usize = 1 << 16;
+
+/// A 4x4 board packed into a `u64` for fast search.
+///
+/// Every cell is a four bit exponent, `0` for an empty cell and `n` for the
+/// tile `2^n`. The cell `(row, column)` is stored in the bits starting at
+/// `4 * (4 * row + column)`.
+///
+/// Tiles go up to `32768`. Two `32768` tiles do not merge, as `65536` does
+/// not fit into four bits, so [`Bitboard::from_board`] rejects boards with
+/// more than one of them. Every move of an accepted board matches the move
+/// of the [`Board`], but moves can bring a second `32768` tile onto it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Bitboard(u64);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitboardError {
+    /// The tile is larger than `32768`.
+    TileTooLarge(u64),
+    /// The board holds more than one `32768` tile, which could merge into
+    /// a tile that does not fit.
+    MergeTooLarge,
+}
+
+impl fmt::Display for BitboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BitboardError::TileTooLarge(value) => {
+                write!(f, "{} does not fit into a bitboard", value)
+            }
+            BitboardError::MergeTooLarge => {
+                write!(f, "two 32768 tiles cannot merge on a bitboard")
+            }
+        }
+    }
+}
+
+impl Error for BitboardError {}
+
+struct Tables {
+    left: Box<[u16]>,
+    right: Box<[u16]>,
+    score: Box<[u32]>,
+}
+
+impl Bitboard {
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Fails for tiles above `32768` and for boards with more than one
+    /// `32768` tile.
+    pub fn from_board(board: Board) -> Result<Self, BitboardError> {
+        let mut raw = 0;
+        let mut largest = 0;
+        for (index, value) in board.to_values().into_iter().flatten().enumerate() {
+            let exponent = match value {
+                0 => 0,
+                _ => value.trailing_zeros(),
+            };
+            if exponent > MAX_EXPONENT as u32 {
+                return Err(BitboardError::TileTooLarge(value));
+            }
+            if exponent == MAX_EXPONENT as u32 {
+                largest += 1;
+            }
+            raw |= (exponent as u64) << (4 * index);
+        }
+        if largest > 1 {
+            return Err(BitboardError::MergeTooLarge);
+        }
+        Ok(Self(raw))
+    }
+
+    pub fn to_board(self) -> Board {
+        let mut values = [[0; 4]; 4];
+        for (row, cells) in values.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                *cell = match self.exponent(row, column) {
+                    0 => 0,
+                    exponent => 1 << exponent,
+                };
+            }
+        }
+        Board::from_values(values).expect("every exponent is a valid board value")
+    }
+
+    pub fn exponent(self, row: usize, column: usize) -> u8 {
+        ((self.0 >> (4 * (4 * row + column))) & 0xF) as u8
+    }
+
+    pub fn count_empty(self) -> usize {
+        (0..16)
+            .filter(|index| (self.0 >> (4 * index)) & 0xF == 0)
+            .count()
+    }
+
+    pub fn make_move(self, direction: Direction) -> Self {
+        self.make_move_with_score(direction).0
+    }
+
+    /// Moves the board and returns the points of all merges, without
+    /// spawning a tile.
+    pub fn make_move_with_score(self, direction: Direction) -> (Self, Score) {
+        let tables = tables();
+        match direction {
+            Direction::Left => self.move_rows(&tables.left),
+            Direction::Right => self.move_rows(&tables.right),
+            Direction::Up => {
+                let (moved, score) = self.transpose().move_rows(&tables.left);
+                (moved.transpose(), score)
+            }
+            Direction::Down => {
+                let (moved, score) = self.transpose().move_rows(&tables.right);
+                (moved.transpose(), score)
+            }
+        }
+    }
+
+    pub fn can_move(self, direction: Direction) -> bool {
+        self.make_move(direction) != self
+    }
+
+    pub fn available_moves(self) -> impl Iterator<Item = Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(move |direction| self.can_move(*direction))
+    }
+
+    pub fn is_game_over(self) -> bool {
+        self.available_moves().next().is_none()
+    }
+
+    fn move_rows(self, moves: &[u16]) -> (Self, Score) {
+        let score_table = &tables().score;
+        let mut raw = 0;
+        let mut score: Score = 0;
+        for row in 0..4 {
+            let line = ((self.0 >> (16 * row)) & ROW_MASK) as usize;
+            raw |= (moves[line] as u64) << (16 * row);
+            score += score_table[line] as Score;
+        }
+        (Self(raw), score)
+    }
+
+    /// Swaps rows and columns, so that vertical moves become row moves.
+    fn transpose(self) -> Self {
+        let x = self.0;
+        let a1 = x & 0xF0F0_0F0F_F0F0_0F0F;
+        let a2 = x & 0x0000_F0F0_0000_F0F0;
+        let a3 = x & 0x0F0F_0000_0F0F_0000;
+        let a = a1 | (a2 << 12) | (a3 >> 12);
+        let b1 = a & 0xFF00_FF00_00FF_00FF;
+        let b2 = a & 0x00FF_00FF_0000_0000;
+        let b3 = a & 0x0000_0000_FF00_FF00;
+        Self(b1 | (b2 >> 24) | (b3 << 24))
+    }
+}
+
+impl TryFrom<Board> for Bitboard {
+    type Error = BitboardError;
+
+    fn try_from(board: Board) -> Result<Self, Self::Error> {
+        Bitboard::from_board(board)
+    }
+}
+
+impl From<Bitboard> for Board {
+    fn from(bitboard: Bitboard) -> Self {
+        bitboard.to_board()
+    }
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut left = vec![0; ROW_COUNT].into_boxed_slice();
+        let mut right = vec![0; ROW_COUNT].into_boxed_slice();
+        let mut score = vec![0; ROW_COUNT].into_boxed_slice();
+        for row in 0..ROW_COUNT {
+            let (moved, points) = move_row_left(unpack_row(row as u16));
+            left[row] = pack_row(moved);
+            score[row] = points;
+            right[reverse_row(row as u16) as usize] = reverse_row(pack_row(moved));
+        }
+        Tables { left, right, score }
+    })
+}
+
+/// Moves the exponents of a row to the left, merging every pair of equal
+/// tiles once.
+fn move_row_left(row: [u8; 4]) -> ([u8; 4], u32) {
+    let mut result = [0; 4];
+    let mut points = 0;
+    let mut target = 0;
+    let mut pending: Option<u8> = None;
+    for exponent in row.into_iter().filter(|exponent| *exponent != 0) {
+        match pending {
+            Some(previous) if previous == exponent && exponent < MAX_EXPONENT => {
+                result[target] = exponent + 1;
+                points += 1 << (exponent + 1);
+                target += 1;
+                pending = None;
+            }
+            Some(previous) => {
+                result[target] = previous;
+                target += 1;
+                pending = Some(exponent);
+            }
+            None => pending = Some(exponent),
+        }
+    }
+    if let Some(previous) = pending {
+        result[target] = previous;
+    }
+    (result, points)
+}
+
+fn unpack_row(row: u16) -> [u8; 4] {
+    [0, 1, 2, 3].map(|column| ((row >> (4 * column)) & 0xF) as u8)
+}
+
+fn pack_row(exponents: [u8; 4]) -> u16 {
+    exponents
+        .iter()
+        .enumerate()
+        .fold(0, |row, (column, exponent)| {
+            row | (*exponent as u16) << (4 * column)
+        })
+}
+
+fn reverse_row(row: u16) -> u16 {
+    (row >> 12) | ((row >> 4) & 0x00F0) | ((row << 4) & 0x0F00) | (row << 12)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitboard::*;
+    use crate::field_gameover::is_game_over;
+    use crate::field_move_and_merge::move_and_merge;
+    use crate::score_calculator::tests::calculate_added_points;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_board(rng: &mut StdRng) -> Board {
+        let mut values = [[0; 4]; 4];
+        for cell in values.iter_mut().flatten() {
+            // Small exponents and empty cells make merges likely
+            let exponent = rng
+                .gen_range(0..=15_u32)
+                .saturating_sub(rng.gen_range(0..8));
+            *cell = if exponent == 0 { 0 } else { 1 << exponent };
+        }
+        Board::from_values(values).unwrap()
+    }
+
+    #[test]
+    fn it_should_round_trip_a_board() {
+        let board =
+            Board::from_values([[2, 0, 0, 0], [0, 4, 0, 0], [0, 0, 8, 0], [0, 0, 0, 32768]])
+                .unwrap();
+        let bitboard = Bitboard::from_board(board).unwrap();
+        assert_eq!(bitboard.exponent(3, 3), 15);
+        assert_eq!(bitboard.count_empty(), 12);
+        assert_eq!(bitboard.to_board(), board);
+    }
+
+    #[test]
+    fn it_should_reject_tiles_which_do_not_fit_into_four_bits() {
+        let board = Board::from_values([[65536, 0, 0, 0], [0; 4], [0; 4], [0; 4]]).unwrap();
+        assert_eq!(
+            Bitboard::try_from(board),
+            Err(BitboardError::TileTooLarge(65536))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_boards_with_two_of_the_largest_tiles() {
+        let board = Board::from_notation("32768.../..../..../...32768").unwrap();
+        assert_eq!(
+            Bitboard::from_board(board),
+            Err(BitboardError::MergeTooLarge)
+        );
+    }
+
+    #[test]
+    fn it_should_transpose_back_and_forth() {
+        let bitboard = Bitboard::from_raw(0x0123_4567_89AB_CDEF);
+        assert_eq!(bitboard.transpose().exponent(1, 0), bitboard.exponent(0, 1));
+        assert_eq!(bitboard.transpose().exponent(3, 2), bitboard.exponent(2, 3));
+        assert_eq!(bitboard.transpose().transpose(), bitboard);
+    }
+
+    #[test]
+    fn it_should_move_the_largest_tile_like_the_field_engine() {
+        let board = Board::from_notation("32768,16384,16384./..../..../...2").unwrap();
+        let bitboard = Bitboard::from_board(board).unwrap();
+        let (moved, score) = bitboard.make_move_with_score(Direction::Left);
+        assert_eq!(
+            moved.to_board().field(),
+            move_and_merge(board.field(), Direction::Left)
+        );
+        assert_eq!(moved.exponent(0, 1), 15);
+        assert_eq!(score, 32768);
+    }
+
+    #[test]
+    fn it_should_not_merge_the_largest_tiles() {
+        // Two exponents of 15 in the first row, only reachable by moves
+        let bitboard = Bitboard::from_raw(0xFF);
+        assert_eq!(
+            bitboard.make_move_with_score(Direction::Left),
+            (bitboard, 0)
+        );
+    }
+
+    #[test]
+    fn it_should_move_like_the_field_engine() {
+        let mut rng = StdRng::seed_from_u64(2048);
+        let (mut largest, mut rejected) = (0, 0);
+        for _ in 0..2000 {
+            let board = random_board(&mut rng);
+            let bitboard = match Bitboard::from_board(board) {
+                Ok(bitboard) => bitboard,
+                Err(BitboardError::MergeTooLarge) => {
+                    rejected += 1;
+                    continue;
+                }
+                Err(error) => panic!("{} {:?}", error, board),
+            };
+            if (0..16).any(|index| bitboard.exponent(index / 4, index % 4) == 15) {
+                largest += 1;
+            }
+            for direction in Direction::ALL {
+                let moved = move_and_merge(board.field(), direction);
+                let (bit_moved, bit_score) = bitboard.make_move_with_score(direction);
+                assert_eq!(
+                    bit_moved.to_board().field(),
+                    moved,
+                    "{:?} {:?}",
+                    board,
+                    direction
+                );
+                assert_eq!(
+                    bit_score,
+                    calculate_added_points(board.field(), direction),
+                    "{:?} {:?}",
+                    board,
+                    direction
+                );
+            }
+            assert_eq!(bitboard.is_game_over(), is_game_over(board.field()));
+        }
+        // Both kinds of boards with the largest tile were drawn
+        assert!(largest > 0 && rejected > 0, "{} {}", largest, rejected);
+    }
+
+    #[test]
+    fn it_should_detect_the_end_of_the_game_like_the_field_engine() {
+        let mut rng = StdRng::seed_from_u64(4096);
+        for _ in 0..2000 {
+            let mut values = [[0; 4]; 4];
+            for cell in values.iter_mut().flatten() {
+                *cell = 1 << rng.gen_range(1..4);
+            }
+            let board = Board::from_values(values).unwrap();
+            let bitboard = Bitboard::from_board(board).unwrap();
+            assert_eq!(bitboard.is_game_over(), is_game_over(board.field()));
+        }
+    }
+}
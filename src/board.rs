@@ -0,0 +1,406 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board_value::BoardValue;
+use crate::field::Field;
+use crate::field_add_random_value::get_coordinates_of_empty_cells;
+use crate::game::ExternalFieldRepresentation;
+
+/// A cell position as `(row, column)`, both starting at `0` in the top left.
+pub type Coordinate = (usize, usize);
+
+/// A validated board of `W` columns and `H` rows which can be constructed
+/// outside of the crate, e.g. to start a [`Game`](crate::game::Game) from a
+/// known position. The classic board is 4x4; square sizes from 3x3 up to 8x8
+/// and rectangles like 4x6 are meant to be played.
+///
+/// Every cell is either empty (`0`) or a power of two which is at least `2`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Board<const W: usize = 4, const H: usize = W> {
+    field: Field<W, H>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BoardError {
+    /// The value is neither `0` nor a power of two greater than `1`.
+    InvalidValue(u64),
+    /// The number of given cells does not match the board size.
+    WrongLength { expected: usize, actual: usize },
+    /// The width of an external representation does not match the board.
+    WrongWidth { expected: usize, actual: usize },
+    /// The coordinate lies outside of the board.
+    OutOfBounds(Coordinate),
+    /// There is no empty cell left to place a value.
+    Full,
+    /// A token of a textual board could not be parsed as a number.
+    InvalidNumber(String),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::InvalidValue(value) => {
+                write!(f, "{} is not a valid board value", value)
+            }
+            BoardError::WrongLength { expected, actual } => {
+                write!(f, "expected {} cells but got {}", expected, actual)
+            }
+            BoardError::WrongWidth { expected, actual } => {
+                write!(f, "expected rows of {} cells but got {}", expected, actual)
+            }
+            BoardError::OutOfBounds((row, column)) => {
+                write!(f, "({}, {}) is outside of the board", row, column)
+            }
+            BoardError::Full => write!(f, "the board has no empty cell"),
+            BoardError::InvalidNumber(token) => write!(f, "{:?} is not a number", token),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+impl<const W: usize, const H: usize> Board<W, H> {
+    /// The number of columns.
+    pub const WIDTH: usize = W;
+    /// The number of rows.
+    pub const HEIGHT: usize = H;
+
+    pub fn from_values(values: [[u64; W]; H]) -> Result<Self, BoardError> {
+        let mut field: Field<W, H> = [[BoardValue::new(0); W]; H];
+        for (row, row_values) in values.iter().enumerate() {
+            for (column, value) in row_values.iter().enumerate() {
+                field[row][column] = to_board_value(*value)?;
+            }
+        }
+        Ok(Self { field })
+    }
+
+    /// Builds a board from the representation returned by
+    /// [`Game::get_field`](crate::game::Game::get_field). Fails if its width
+    /// or number of cells differs from the board.
+    pub fn from_external(external: &ExternalFieldRepresentation) -> Result<Self, BoardError> {
+        if external.width != W {
+            return Err(BoardError::WrongWidth {
+                expected: W,
+                actual: external.width,
+            });
+        }
+        Self::from_row_major(&external.values)
+    }
+
+    /// Builds a board from `W * H` values, row after row.
+    pub(crate) fn from_row_major(values: &[u64]) -> Result<Self, BoardError> {
+        if values.len() != W * H {
+            return Err(BoardError::WrongLength {
+                expected: W * H,
+                actual: values.len(),
+            });
+        }
+        let mut rows = [[0; W]; H];
+        for (index, value) in values.iter().enumerate() {
+            rows[index / W][index % W] = *value;
+        }
+        Self::from_values(rows)
+    }
+
+    pub fn to_values(&self) -> [[u64; W]; H] {
+        self.field.map(|row| row.map(|cell| cell.value()))
+    }
+
+    pub fn to_external(&self) -> ExternalFieldRepresentation {
+        ExternalFieldRepresentation {
+            width: W,
+            values: self.to_values().into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn get(&self, (row, column): Coordinate) -> Option<u64> {
+        self.field
+            .get(row)
+            .and_then(|cells| cells.get(column))
+            .map(|cell| cell.value())
+    }
+
+    /// Returns a copy of the board with `value` placed at `coordinate`.
+    pub fn with_tile(&self, coordinate: Coordinate, value: u64) -> Result<Self, BoardError> {
+        if self.get(coordinate).is_none() {
+            return Err(BoardError::OutOfBounds(coordinate));
+        }
+        let mut field = self.field;
+        field[coordinate.0][coordinate.1] = to_board_value(value)?;
+        Ok(Self { field })
+    }
+
+    /// Coordinates of all empty cells in row-major order.
+    pub fn empty_cells(&self) -> Vec<Coordinate> {
+        get_coordinates_of_empty_cells(self.field)
+    }
+
+    pub(crate) fn field(&self) -> Field<W, H> {
+        self.field
+    }
+}
+
+pub(crate) fn to_board_value(value: u64) -> Result<BoardValue, BoardError> {
+    BoardValue::from_value(value).map_err(|_| BoardError::InvalidValue(value))
+}
+
+impl<const W: usize, const H: usize> From<Field<W, H>> for Board<W, H> {
+    fn from(field: Field<W, H>) -> Self {
+        Self { field }
+    }
+}
+
+impl<const W: usize, const H: usize> TryFrom<[[u64; W]; H]> for Board<W, H> {
+    type Error = BoardError;
+
+    fn try_from(values: [[u64; W]; H]) -> Result<Self, Self::Error> {
+        Board::from_values(values)
+    }
+}
+
+impl<const W: usize, const H: usize> TryFrom<&ExternalFieldRepresentation> for Board<W, H> {
+    type Error = BoardError;
+
+    fn try_from(external: &ExternalFieldRepresentation) -> Result<Self, Self::Error> {
+        Board::from_external(external)
+    }
+}
+
+/// Parses `W * H` numbers separated by whitespace or commas, e.g.
+/// `"2 0 0 0\n0 4 0 0\n0 0 0 0\n0 0 0 2"` for a 4x4 board. A comma must
+/// stand between two numbers, whitespace around it is allowed.
+impl<const W: usize, const H: usize> FromStr for Board<W, H> {
+    type Err = BoardError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parts = text.split(',').collect::<Vec<&str>>();
+        if parts.len() > 1 {
+            if let Some(empty) = parts.iter().find(|part| part.trim().is_empty()) {
+                return Err(BoardError::InvalidNumber(empty.to_string()));
+            }
+        }
+        let values = parts
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .map(|token| {
+                token
+                    .parse::<u64>()
+                    .map_err(|_| BoardError::InvalidNumber(token.to_string()))
+            })
+            .collect::<Result<Vec<u64>, BoardError>>()?;
+        Board::from_row_major(&values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::*;
+    use crate::field::tests::*;
+
+    const FIELD: Field = [
+        [TWO, X, X, X],
+        [X, FOUR, X, X],
+        [X, X, EIGHT, X],
+        [X, X, X, SIXTEEN],
+    ];
+    const VALUES: [[u64; 4]; 4] = [[2, 0, 0, 0], [0, 4, 0, 0], [0, 0, 8, 0], [0, 0, 0, 16]];
+
+    #[cfg(test)]
+    mod from_values {
+        use crate::board::tests::*;
+
+        #[test]
+        fn it_should_create_board_with_given_values() {
+            assert_eq!(Board::from_values(VALUES).unwrap().field(), FIELD);
+        }
+
+        #[test]
+        fn it_should_keep_values_beyond_32_bits() {
+            let mut values = VALUES;
+            values[0][1] = 1 << 40;
+            assert_eq!(
+                Board::from_values(values).unwrap().get((0, 1)),
+                Some(1 << 40)
+            );
+        }
+
+        #[test]
+        fn it_should_reject_values_which_are_not_a_power_of_two() {
+            let mut values = VALUES;
+            values[1][2] = 6;
+            assert_eq!(Board::from_values(values), Err(BoardError::InvalidValue(6)));
+        }
+
+        #[test]
+        fn it_should_reject_one() {
+            let mut values = VALUES;
+            values[3][3] = 1;
+            assert_eq!(Board::try_from(values), Err(BoardError::InvalidValue(1)));
+        }
+    }
+
+    #[cfg(test)]
+    mod from_external {
+        use crate::board::tests::*;
+
+        fn external(width: usize, values: &[u64]) -> ExternalFieldRepresentation {
+            ExternalFieldRepresentation {
+                width,
+                values: values.to_vec(),
+            }
+        }
+
+        #[test]
+        fn it_should_create_board_from_row_major_values() {
+            let values = [2, 0, 0, 0, 0, 4, 0, 0, 0, 0, 8, 0, 0, 0, 0, 16];
+            assert_eq!(
+                Board::from_external(&external(4, &values)).unwrap().field(),
+                FIELD
+            );
+        }
+
+        #[test]
+        fn it_should_reject_wrong_length() {
+            assert_eq!(
+                Board::<4>::from_external(&external(4, &[2, 0, 0])),
+                Err(BoardError::WrongLength {
+                    expected: 16,
+                    actual: 3
+                })
+            );
+        }
+
+        #[test]
+        fn it_should_expect_the_cells_of_its_size() {
+            assert_eq!(
+                Board::<3>::from_external(&external(3, &[0; 16])),
+                Err(BoardError::WrongLength {
+                    expected: 9,
+                    actual: 16
+                })
+            );
+            assert!(Board::<3>::from_external(&external(3, &[2, 0, 0, 0, 4, 0, 0, 0, 8])).is_ok());
+        }
+
+        #[test]
+        fn it_should_reject_rows_of_another_width() {
+            // The same number of cells as a 4x4 board, but two rows of eight
+            let wide = Board::<8, 2>::from_values([[2, 0, 0, 0, 0, 0, 0, 4], [0; 8]]).unwrap();
+            assert_eq!(
+                Board::<4>::try_from(&wide.to_external()),
+                Err(BoardError::WrongWidth {
+                    expected: 4,
+                    actual: 8
+                })
+            );
+        }
+
+        #[test]
+        fn it_should_fill_rows_of_its_width() {
+            let board = Board::<3, 2>::from_external(&external(3, &[2, 0, 0, 0, 0, 4])).unwrap();
+            assert_eq!(board.to_values(), [[2, 0, 0], [0, 0, 4]]);
+            assert_eq!(board.get((1, 2)), Some(4));
+            assert_eq!(board.get((2, 0)), None);
+        }
+
+        #[test]
+        fn it_should_record_the_width_in_the_external_representation() {
+            let external = Board::<3, 2>::from_values([[2, 0, 0], [0, 0, 4]])
+                .unwrap()
+                .to_external();
+            assert_eq!(external.width, 3);
+            assert_eq!(external.height(), 2);
+            assert_eq!(
+                external.rows().collect::<Vec<&[u64]>>(),
+                vec![&[2, 0, 0][..], &[0, 0, 4][..]]
+            );
+        }
+
+        #[test]
+        fn it_should_round_trip_external_representation() {
+            let board = Board::from(FIELD);
+            assert_eq!(Board::from_external(&board.to_external()), Ok(board));
+        }
+    }
+
+    #[cfg(test)]
+    mod with_tile {
+        use crate::board::tests::*;
+
+        #[test]
+        fn it_should_place_the_value_at_the_coordinate() {
+            let board = Board::from(FIELD).with_tile((0, 3), 4).unwrap();
+            assert_eq!(board.get((0, 3)), Some(4));
+            assert_eq!(board.empty_cells().len(), 11);
+        }
+
+        #[test]
+        fn it_should_reject_coordinates_outside_of_the_board() {
+            assert_eq!(
+                Board::from(FIELD).with_tile((4, 0), 2),
+                Err(BoardError::OutOfBounds((4, 0)))
+            );
+        }
+
+        #[test]
+        fn it_should_reject_invalid_values() {
+            assert_eq!(
+                Board::from(FIELD).with_tile((0, 1), 3),
+                Err(BoardError::InvalidValue(3))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod from_str {
+        use crate::board::tests::*;
+
+        #[test]
+        fn it_should_parse_whitespace_separated_values() {
+            let board: Board = "2 0 0 0\n0 4 0 0\n0 0 8 0\n0 0 0 16".parse().unwrap();
+            assert_eq!(board.to_values(), VALUES);
+        }
+
+        #[test]
+        fn it_should_parse_comma_separated_values() {
+            let board: Board = "2,0,0,0, 0,4,0,0, 0,0,8,0, 0,0,0,16".parse().unwrap();
+            assert_eq!(board.to_values(), VALUES);
+        }
+
+        #[test]
+        fn it_should_reject_commas_without_a_number() {
+            for text in [
+                "2,,0,0 0,4,0,0 0,0,8,0 0,0,0,16",
+                ",2,0,0,0 0,4,0,0 0,0,8,0 0,0,0,16",
+                "2,0,0,0 0,4,0,0 0,0,8,0 0,0,0,16,",
+            ] {
+                assert!(
+                    matches!(text.parse::<Board>(), Err(BoardError::InvalidNumber(_))),
+                    "{}",
+                    text
+                );
+            }
+        }
+
+        #[test]
+        fn it_should_reject_tokens_which_are_not_numbers() {
+            assert_eq!(
+                "2 0 0 x".parse::<Board>(),
+                Err(BoardError::InvalidNumber("x".to_string()))
+            );
+        }
+
+        #[test]
+        fn it_should_reject_too_few_values() {
+            assert_eq!(
+                "2 0 0 0".parse::<Board>(),
+                Err(BoardError::WrongLength {
+                    expected: 16,
+                    actual: 4
+                })
+            );
+        }
+    }
+}
@@ -1,21 +1,98 @@
+use std::error::Error;
 use std::fmt;
 
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+/// The largest exponent of a tile, whose value `2^63` still fits into a
+/// `u64`.
+pub const MAX_EXPONENT: u8 = 63;
+
+/// A single cell of a board, stored as the exponent of its value: `0` for an
+/// empty cell and `n` for the tile `2^n`.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct BoardValue {
-    value: u32,
+    exponent: u8,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoardValueError {
+    /// The value is neither `0` nor a power of two greater than `1`.
+    InvalidValue(u64),
+    /// The exponent is larger than [`MAX_EXPONENT`].
+    InvalidExponent(u8),
+    /// Two tiles of the largest value cannot be merged.
+    MaximumReached,
 }
 
+impl fmt::Display for BoardValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardValueError::InvalidValue(value) => {
+                write!(f, "{} is not a valid board value", value)
+            }
+            BoardValueError::InvalidExponent(exponent) => {
+                write!(f, "2^{} is too large for a board value", exponent)
+            }
+            BoardValueError::MaximumReached => write!(f, "the largest board value cannot merge"),
+        }
+    }
+}
+
+impl Error for BoardValueError {}
+
 impl BoardValue {
-    pub const fn new(value: u32) -> BoardValue {
-        Self { value }
+    pub const EMPTY: BoardValue = BoardValue { exponent: 0 };
+
+    /// Only for values known to be valid, e.g. in constants. Panics for any
+    /// other value.
+    pub(crate) const fn new(value: u64) -> BoardValue {
+        assert!(value == 0 || (value > 1 && value.is_power_of_two()));
+        Self {
+            exponent: if value == 0 {
+                0
+            } else {
+                value.trailing_zeros() as u8
+            },
+        }
     }
 
-    pub fn get_value(self) -> u32{
-        self.value
+    pub fn from_value(value: u64) -> Result<BoardValue, BoardValueError> {
+        if value == 0 || (value > 1 && value.is_power_of_two()) {
+            Ok(Self::new(value))
+        } else {
+            Err(BoardValueError::InvalidValue(value))
+        }
     }
 
-    pub fn duplicate(self) -> BoardValue {
-        BoardValue::new(self.value * 2)
+    pub fn from_exponent(exponent: u8) -> Result<BoardValue, BoardValueError> {
+        if exponent > MAX_EXPONENT {
+            return Err(BoardValueError::InvalidExponent(exponent));
+        }
+        Ok(Self { exponent })
+    }
+
+    pub fn exponent(self) -> u8 {
+        self.exponent
+    }
+
+    pub fn value(self) -> u64 {
+        match self.exponent {
+            0 => 0,
+            exponent => 1 << exponent,
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.exponent == 0
+    }
+
+    /// The value of two merged tiles. An empty cell stays empty.
+    pub fn duplicate(self) -> Result<BoardValue, BoardValueError> {
+        match self.exponent {
+            0 => Ok(self),
+            MAX_EXPONENT => Err(BoardValueError::MaximumReached),
+            exponent => Ok(Self {
+                exponent: exponent + 1,
+            }),
+        }
     }
 }
 
@@ -27,30 +104,81 @@ impl Default for BoardValue {
 
 impl fmt::Debug for BoardValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.value)
+        write!(f, "{}", self.value())
+    }
+}
+
+impl fmt::Display for BoardValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::board_value::BoardValue;
+    use crate::board_value::*;
 
     #[test]
     fn it_should_init_default() {
-        assert_eq!(BoardValue::default(),BoardValue { value: 2 });
+        assert_eq!(BoardValue::default(), BoardValue { exponent: 1 });
     }
 
     #[test]
     fn it_should_create_given_value() {
-        assert_eq!(BoardValue::new(4), BoardValue { value: 4 });
+        assert_eq!(BoardValue::new(4), BoardValue { exponent: 2 });
+        assert_eq!(BoardValue::new(4).value(), 4);
     }
 
     #[test]
     fn it_should_duplicate_the_value() {
         // Possibility for Rust QuickCheck: https://github.com/BurntSushi/quickcheck
-        assert_eq!(BoardValue::new(4).duplicate(), BoardValue { value: 8 });
-        assert_eq!(BoardValue::new(8).duplicate(), BoardValue { value: 16 });
+        assert_eq!(BoardValue::new(4).duplicate(), Ok(BoardValue::new(8)));
+        assert_eq!(BoardValue::new(8).duplicate(), Ok(BoardValue::new(16)));
+        assert_eq!(BoardValue::EMPTY.duplicate(), Ok(BoardValue::EMPTY));
+    }
+
+    #[test]
+    fn it_should_duplicate_beyond_32_bits() {
+        let value = BoardValue::from_value(1 << 31)
+            .unwrap()
+            .duplicate()
+            .unwrap();
+        assert_eq!(value.value(), 1 << 32);
+    }
+
+    #[test]
+    fn it_should_not_duplicate_the_largest_value() {
+        let largest = BoardValue::from_exponent(MAX_EXPONENT).unwrap();
+        assert_eq!(largest.value(), 1 << 63);
+        assert_eq!(largest.duplicate(), Err(BoardValueError::MaximumReached));
+    }
+
+    #[test]
+    fn it_should_reject_values_which_are_not_a_power_of_two() {
+        assert_eq!(
+            BoardValue::from_value(3),
+            Err(BoardValueError::InvalidValue(3))
+        );
+        assert_eq!(
+            BoardValue::from_value(1),
+            Err(BoardValueError::InvalidValue(1))
+        );
+        assert_eq!(BoardValue::from_value(0), Ok(BoardValue::EMPTY));
+    }
+
+    #[test]
+    fn it_should_reject_too_large_exponents() {
+        assert_eq!(
+            BoardValue::from_exponent(64),
+            Err(BoardValueError::InvalidExponent(64))
+        );
+    }
+
+    #[test]
+    fn it_should_display_the_value() {
+        assert_eq!(BoardValue::new(2048).to_string(), "2048");
+        assert_eq!(BoardValue::EMPTY.to_string(), "0");
     }
 
 }
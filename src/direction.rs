@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise starting with [`Direction::Up`].
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        self.rotate_clockwise().rotate_clockwise()
+    }
+
+    pub fn rotate_clockwise(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn rotate_counterclockwise(self) -> Direction {
+        self.opposite().rotate_clockwise()
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Direction::Up => "up",
+            Direction::Right => "right",
+            Direction::Down => "down",
+            Direction::Left => "left",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseDirectionError(String);
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is not a direction", self.0)
+    }
+}
+
+impl Error for ParseDirectionError {}
+
+/// Accepts the name (`"up"`), the WASD key (`"w"`) and the arrow key name
+/// (`"ArrowUp"`) of a direction, ignoring case.
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let normalized = text.trim().to_lowercase().replace(['_', '-', ' '], "");
+        match normalized.as_str() {
+            "up" | "w" | "arrowup" | "uparrow" | "↑" => Ok(Direction::Up),
+            "right" | "d" | "arrowright" | "rightarrow" | "→" => Ok(Direction::Right),
+            "down" | "s" | "arrowdown" | "downarrow" | "↓" => Ok(Direction::Down),
+            "left" | "a" | "arrowleft" | "leftarrow" | "←" => Ok(Direction::Left),
+            _ => Err(ParseDirectionError(text.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::*;
+
+    #[test]
+    fn it_should_contain_every_direction_once() {
+        for direction in Direction::ALL {
+            assert_eq!(
+                Direction::ALL.iter().filter(|d| **d == direction).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_return_the_opposite_direction() {
+        assert_eq!(Direction::Up.opposite(), Direction::Down);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+    }
+
+    #[test]
+    fn it_should_rotate_back_and_forth() {
+        for direction in Direction::ALL {
+            assert_eq!(
+                direction.rotate_clockwise().rotate_counterclockwise(),
+                direction
+            );
+        }
+        assert_eq!(Direction::Left.rotate_clockwise(), Direction::Up);
+        assert_eq!(Direction::Left.rotate_counterclockwise(), Direction::Down);
+    }
+
+    #[test]
+    fn it_should_parse_names_keys_and_arrows() {
+        assert_eq!("up".parse(), Ok(Direction::Up));
+        assert_eq!("W".parse(), Ok(Direction::Up));
+        assert_eq!("ArrowLeft".parse(), Ok(Direction::Left));
+        assert_eq!("arrow_down".parse(), Ok(Direction::Down));
+        assert_eq!("d".parse(), Ok(Direction::Right));
+        assert_eq!(" Right ".parse(), Ok(Direction::Right));
+    }
+
+    #[test]
+    fn it_should_parse_its_own_display_output() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.to_string().parse(), Ok(direction));
+        }
+    }
+
+    #[test]
+    fn it_should_reject_unknown_names() {
+        assert_eq!(
+            "diagonal".parse::<Direction>(),
+            Err(ParseDirectionError("diagonal".to_string()))
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use std::mem;
+use std::thread;
+
+use rand::SeedableRng;
+
+use crate::direction::Direction;
+use crate::game::{Game, GameRng};
+use crate::game_status::GameStatus;
+use crate::rules::Rules;
+
+/// The encoded board, see [`Encoding`].
+pub type Observation = Vec<f32>;
+
+/// How an [`Env`] encodes the board into an [`Observation`]. All encodings
+/// list the cells row by row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Encoding {
+    /// The tile values, `0` for an empty cell.
+    Raw,
+    /// The exponents of the tiles, `0` for an empty cell and `11` for `2048`.
+    #[default]
+    Log2,
+    /// One plane of all cells per exponent, plane after plane: a cell is `1`
+    /// in the plane of its exponent and `0` in all others. Exponents beyond
+    /// the last plane count for the last plane.
+    OneHot(usize),
+}
+
+/// What happened during an [`Env::step`] besides the reward.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Info {
+    /// Whether the action changed the board. Actions which do not are
+    /// ignored and rewarded with `0`.
+    pub moved: bool,
+    /// Whether the target tile of the rules was reached with this step.
+    pub won: bool,
+    pub score: u64,
+    pub max_tile: u64,
+    /// The actions of [`Direction::ALL`] which would change the board now.
+    pub action_mask: [bool; 4],
+    /// The observation of the finished game, only set by a [`VecEnv`]
+    /// which already reset the environment.
+    pub terminal_observation: Option<Observation>,
+}
+
+/// A game as a reinforcement learning environment: the actions are the
+/// directions, the reward is the score a move adds and an episode ends once
+/// no move is left. Reaching the target tile does not end it.
+pub struct Env<const W: usize = 4, const H: usize = W> {
+    game: Game<GameRng, W, H>,
+    rules: Rules,
+    encoding: Encoding,
+}
+
+impl Env {
+    /// A classic 4x4 environment with [`Encoding::Log2`], reset with the seed
+    /// `0`.
+    pub fn new() -> Self {
+        Self::with_rules(Rules::classic())
+    }
+}
+
+impl<const W: usize, const H: usize> Env<W, H> {
+    /// An environment of any size, e.g. `Env::<3>::with_rules(..)` for a 3x3
+    /// board, reset with the seed `0`.
+    pub fn with_rules(rules: Rules) -> Self {
+        Self {
+            game: new_game(&rules, 0),
+            rules,
+            encoding: Encoding::default(),
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Starts a new episode, the same seed always gives the same episode
+    /// for the same actions.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = new_game(&self.rules, seed);
+        self.observation()
+    }
+
+    /// Moves in the direction and returns the observation, the reward,
+    /// whether the episode is over and more details.
+    pub fn step(&mut self, action: Direction) -> (Observation, f64, bool, Info) {
+        let score = self.game.get_score();
+        let mut info = Info::default();
+        if let Ok(outcome) = self.game.make_move(action) {
+            info.moved = outcome.moved;
+            info.won = outcome.won && self.game.status() == GameStatus::Won;
+        }
+        if self.game.status() == GameStatus::Won {
+            // Cannot fail for a won game
+            let _ = self.game.continue_after_win();
+        }
+        info.score = self.game.get_score();
+        info.max_tile = self.max_tile();
+        info.action_mask = self.action_mask();
+        let reward = (info.score - score) as f64;
+        (self.observation(), reward, self.is_done(), info)
+    }
+
+    /// The actions of [`Direction::ALL`] which would change the board.
+    pub fn action_mask(&self) -> [bool; 4] {
+        Direction::ALL.map(|direction| self.game.can_move(direction))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.game.status() == GameStatus::Lost
+    }
+
+    pub fn observation(&self) -> Observation {
+        let values = self.game.get_board().to_values();
+        let cells = values.iter().flatten();
+        match self.encoding {
+            Encoding::Raw => cells.map(|value| *value as f32).collect(),
+            Encoding::Log2 => cells.map(|value| exponent(*value) as f32).collect(),
+            Encoding::OneHot(planes) => {
+                let mut observation = vec![0.0; planes * W * H];
+                if planes > 0 {
+                    for (index, value) in cells.enumerate() {
+                        let plane = exponent(*value).min(planes - 1);
+                        observation[plane * W * H + index] = 1.0;
+                    }
+                }
+                observation
+            }
+        }
+    }
+
+    /// The number of values in every observation.
+    pub fn observation_len(&self) -> usize {
+        match self.encoding {
+            Encoding::Raw | Encoding::Log2 => W * H,
+            Encoding::OneHot(planes) => planes * W * H,
+        }
+    }
+
+    pub fn game(&self) -> &Game<GameRng, W, H> {
+        &self.game
+    }
+
+    fn max_tile(&self) -> u64 {
+        let values = self.game.get_board().to_values();
+        values.iter().flatten().max().copied().unwrap_or(0)
+    }
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn new_game<const W: usize, const H: usize>(rules: &Rules, seed: u64) -> Game<GameRng, W, H> {
+    Game::with_rules_and_rng(rules.clone(), GameRng::seed_from_u64(seed))
+}
+
+fn exponent(value: u64) -> usize {
+    if value == 0 {
+        0
+    } else {
+        value.trailing_zeros() as usize
+    }
+}
+
+/// Many environments stepped together, one action for each, split over the
+/// available threads. A finished episode is reset right away with the next
+/// unused seed, its last observation is kept in
+/// [`Info::terminal_observation`].
+pub struct VecEnv<const W: usize = 4, const H: usize = W> {
+    envs: Vec<Env<W, H>>,
+    next_seed: u64,
+}
+
+impl VecEnv {
+    /// `count` classic 4x4 environments.
+    pub fn new(count: usize) -> Self {
+        Self::with_rules(count, Rules::classic())
+    }
+}
+
+impl<const W: usize, const H: usize> VecEnv<W, H> {
+    pub fn with_rules(count: usize, rules: Rules) -> Self {
+        Self {
+            envs: (0..count).map(|_| Env::with_rules(rules.clone())).collect(),
+            next_seed: count as u64,
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.envs = self
+            .envs
+            .into_iter()
+            .map(|env| env.with_encoding(encoding))
+            .collect();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    /// Resets the environments with the seeds `seed`, `seed + 1` and so on.
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.next_seed = seed + self.envs.len() as u64;
+        self.envs
+            .iter_mut()
+            .zip(seed..)
+            .map(|(env, seed)| env.reset(seed))
+            .collect()
+    }
+
+    /// Steps every environment with its action. Panics if the number of
+    /// actions differs from the number of environments.
+    pub fn step(&mut self, actions: &[Direction]) -> Vec<(Observation, f64, bool, Info)> {
+        assert_eq!(
+            actions.len(),
+            self.envs.len(),
+            "expected one action per environment"
+        );
+        let threads = thread::available_parallelism().map_or(1, usize::from);
+        let chunk = self.envs.len().div_ceil(threads).max(1);
+        let mut steps: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .envs
+                .chunks_mut(chunk)
+                .zip(actions.chunks(chunk))
+                .map(|(envs, actions)| scope.spawn(move || step_all(envs, actions)))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("a stepping thread panicked"))
+                .collect()
+        });
+        // Resets in order of the environments, so the seeds do not depend on
+        // the threads
+        for (env, (observation, _, done, info)) in self.envs.iter_mut().zip(&mut steps) {
+            if *done {
+                let reset = env.reset(self.next_seed);
+                info.terminal_observation = Some(mem::replace(observation, reset));
+                info.action_mask = env.action_mask();
+                self.next_seed += 1;
+            }
+        }
+        steps
+    }
+
+    pub fn action_masks(&self) -> Vec<[bool; 4]> {
+        self.envs.iter().map(Env::action_mask).collect()
+    }
+
+    pub fn envs(&self) -> &[Env<W, H>] {
+        &self.envs
+    }
+}
+
+fn step_all<const W: usize, const H: usize>(
+    envs: &mut [Env<W, H>],
+    actions: &[Direction],
+) -> Vec<(Observation, f64, bool, Info)> {
+    envs.iter_mut()
+        .zip(actions)
+        .map(|(env, action)| env.step(*action))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::*;
+
+    /// The first possible action, until the episode is over.
+    fn play_to_the_end(env: &mut Env) -> (Observation, f64, bool, Info) {
+        loop {
+            let action = Direction::ALL[env.action_mask().iter().position(|legal| *legal).unwrap()];
+            let step = env.step(action);
+            if step.2 {
+                return step;
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_reset_reproducibly() {
+        let mut env = Env::new();
+        let observation = env.reset(5);
+        env.step(Direction::Left);
+        assert_eq!(env.reset(5), observation);
+        assert_eq!(observation.len(), 16);
+        assert_eq!(observation.iter().filter(|cell| **cell > 0.0).count(), 2);
+    }
+
+    #[test]
+    fn it_should_reward_the_score_delta() {
+        let mut env = Env::new();
+        env.reset(1);
+        let mut score = 0;
+        for _ in 0..50 {
+            let action = Direction::ALL[env.action_mask().iter().position(|legal| *legal).unwrap()];
+            let (_, reward, _, info) = env.step(action);
+            assert_eq!(reward, (info.score - score) as f64);
+            score = info.score;
+        }
+        assert_eq!(score, env.game().get_score());
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn it_should_detect_moves_which_change_nothing() {
+        let mut env = Env::new();
+        // Some starts cannot move in one direction, e.g. with both tiles in
+        // the top row
+        let index = (0..100)
+            .find_map(|seed| {
+                env.reset(seed);
+                env.action_mask().iter().position(|legal| !legal)
+            })
+            .expect("a start with a move which changes nothing");
+        let before = env.observation();
+        let (observation, reward, done, info) = env.step(Direction::ALL[index]);
+        assert!(!info.moved);
+        assert_eq!((observation, reward, done), (before, 0.0, false));
+    }
+
+    #[test]
+    fn it_should_end_the_episode_when_no_move_is_left() {
+        let mut env = Env::new();
+        env.reset(2);
+        let (_, _, done, info) = play_to_the_end(&mut env);
+        assert!(done);
+        assert_eq!(info.action_mask, [false; 4]);
+        let (_, reward, done, info) = env.step(Direction::Up);
+        assert_eq!((reward, done, info.moved), (0.0, true, false));
+    }
+
+    #[test]
+    fn it_should_keep_playing_after_the_target() {
+        let mut env = Env::with_rules(Rules::classic().with_target(8));
+        env.reset(3);
+        let (_, _, _, info) = play_to_the_end(&mut env);
+        assert!(info.max_tile > 8);
+    }
+
+    #[test]
+    fn it_should_encode_the_board() {
+        let mut env = Env::new().with_encoding(Encoding::Raw);
+        let raw = env.reset(4);
+        let log2: Observation = raw
+            .iter()
+            .map(|value| if *value == 0.0 { 0.0 } else { value.log2() })
+            .collect();
+        assert_eq!(env.with_encoding(Encoding::Log2).observation(), log2);
+    }
+
+    #[test]
+    fn it_should_encode_the_board_as_one_hot_planes() {
+        let mut env = Env::new().with_encoding(Encoding::OneHot(2));
+        let observation = env.reset(4);
+        assert_eq!(observation.len(), 32);
+        assert_eq!(env.observation_len(), 32);
+        // Every cell is set in exactly one plane, tiles beyond the first
+        // plane all in the last one
+        for cell in 0..16 {
+            assert_eq!(observation[cell] + observation[16 + cell], 1.0);
+        }
+        assert_eq!(observation[16..].iter().sum::<f32>(), 2.0);
+    }
+
+    #[test]
+    fn it_should_step_many_environments() {
+        let mut envs = VecEnv::new(3);
+        let observations = envs.reset(10);
+        assert_eq!(observations.len(), 3);
+        let mut single = Env::new();
+        assert_eq!(observations[1], single.reset(11));
+
+        let steps = envs.step(&[Direction::Left; 3]);
+        assert_eq!(steps[1], single.step(Direction::Left));
+        assert_eq!(envs.action_masks()[1], single.action_mask());
+    }
+
+    #[test]
+    fn it_should_step_like_single_environments_on_many_threads() {
+        let mut envs = VecEnv::new(32);
+        envs.reset(0);
+        let mut singles: Vec<Env> = (0..32).map(|_| Env::new()).collect();
+        for (seed, env) in singles.iter_mut().enumerate() {
+            env.reset(seed as u64);
+        }
+        let mut next_seed = 32;
+        for step in 0..300 {
+            let actions: Vec<Direction> = (0..32)
+                .map(|index| Direction::ALL[(index + step) % 4])
+                .collect();
+            for (index, (observation, reward, done, info)) in
+                envs.step(&actions).into_iter().enumerate()
+            {
+                let single = &mut singles[index];
+                let (mut expected, expected_reward, expected_done, _) = single.step(actions[index]);
+                if expected_done {
+                    assert_eq!(info.terminal_observation, Some(expected));
+                    expected = single.reset(next_seed);
+                    next_seed += 1;
+                }
+                assert_eq!(
+                    (observation, reward, done),
+                    (expected, expected_reward, expected_done)
+                );
+            }
+        }
+        // Some episodes ended and were reset on the way
+        assert!(next_seed > 32);
+    }
+
+    #[test]
+    fn it_should_reset_finished_environments() {
+        let mut envs = VecEnv::new(1);
+        envs.reset(0);
+        loop {
+            let mask = envs.action_masks()[0];
+            let action = Direction::ALL[mask.iter().position(|legal| *legal).unwrap()];
+            let (observation, _, done, info) = envs.step(&[action]).remove(0);
+            if done {
+                assert!(info.terminal_observation.is_some());
+                assert_eq!(observation, Env::new().reset(1));
+                assert_eq!(envs.envs()[0].game().get_score(), 0);
+                break;
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use crate::board_value::BoardValue;
 
-pub type Row = [BoardValue; 4];
-pub type Field = [Row; 4];
+pub type Row<const N: usize = 4> = [BoardValue; N];
+/// `H` rows of `W` cells each.
+pub type Field<const W: usize = 4, const H: usize = W> = [Row<W>; H];
 
 #[cfg(test)]
 pub mod tests {
+    use crate::board::Board;
     use crate::field::*;
     pub const X: BoardValue = BoardValue::new(0);
     pub const TWO: BoardValue = BoardValue::new(2);
@@ -12,4 +14,9 @@ pub mod tests {
     pub const EIGHT: BoardValue = BoardValue::new(8);
     pub const SIXTEEN: BoardValue = BoardValue::new(16);
     pub const EMPTY_FIELD: Field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
+
+    /// The 4x4 field of a board in notation, e.g. `2.4./..../8.../...2`.
+    pub fn field(notation: &str) -> Field {
+        Board::from_notation(notation).unwrap().field()
+    }
 }
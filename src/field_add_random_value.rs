@@ -1,42 +1,58 @@
+use crate::board::{BoardError, Coordinate};
 use crate::board_value::BoardValue;
 use crate::field::Field;
 use crate::random::RandomNumber;
 
-type Coordinate = (usize, usize);
-
-pub fn add_value(
-    field: Field,
+pub fn add_value<const W: usize, const H: usize>(
+    field: Field<W, H>,
     random_number_value: RandomNumber,
     random_number_position: RandomNumber,
-) -> Field {
+) -> Result<Field<W, H>, BoardError> {
+    add_given_value(
+        field,
+        get_new_board_value(random_number_value),
+        random_number_position,
+    )
+}
+
+pub fn add_given_value<const W: usize, const H: usize>(
+    field: Field<W, H>,
+    new_board_value: BoardValue,
+    random_number_position: RandomNumber,
+) -> Result<Field<W, H>, BoardError> {
     let mut new_field = field;
-    let new_board_value = get_new_board_value(random_number_value);
-    let (row, column) = get_coordinate(field, random_number_position);
+    let (row, column) = get_coordinate(field, random_number_position)?;
     new_field[row][column] = new_board_value;
-    new_field
+    Ok(new_field)
 }
 
-fn get_coordinate(field: Field, random_number: RandomNumber) -> Coordinate {
+fn get_coordinate<const W: usize, const H: usize>(
+    field: Field<W, H>,
+    random_number: RandomNumber,
+) -> Result<Coordinate, BoardError> {
     let vec: Vec<Coordinate> = get_coordinates_of_empty_cells(field);
+    if vec.is_empty() {
+        return Err(BoardError::Full);
+    }
     let coordinate_index = get_index_for_coordinate(random_number, vec.len());
-    vec[coordinate_index]
+    Ok(vec[coordinate_index])
 }
 
 fn get_index_for_coordinate(random_number: RandomNumber, free_coordinate_count: usize) -> usize {
-    // There is no 100% even distribution!
-    // Through the rounding, the last and first coordinate have a lower chance to be selected
-    // since they only have one half of the rounding.
-    let number_for_coordinate = (free_coordinate_count as f32 * random_number).round();
-    let index_for_cooridnate = (number_for_coordinate - 1.0) as usize;
-    index_for_cooridnate
+    // Every coordinate covers an equally sized interval of [0, 1).
+    // The clamp only guards against float rounding for numbers just below 1.
+    let index_for_coordinate = (free_coordinate_count as f32 * random_number).floor() as usize;
+    index_for_coordinate.min(free_coordinate_count - 1)
 }
 
-fn get_coordinates_of_empty_cells(field: Field) -> Vec<Coordinate> {
+pub fn get_coordinates_of_empty_cells<const W: usize, const H: usize>(
+    field: Field<W, H>,
+) -> Vec<Coordinate> {
     const X: BoardValue = BoardValue::new(0);
     let mut vec: Vec<Coordinate> = Vec::new();
-    for row in 0..4 {
-        for column in 0..4 {
-            if field[row][column] == X {
+    for (row, cells) in field.iter().enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            if *cell == X {
                 vec.push((row, column))
             }
         }
@@ -49,13 +65,11 @@ fn get_new_board_value(random_number: RandomNumber) -> BoardValue {
     const FOUR: BoardValue = BoardValue::new(4);
 
     // Asumption: the four appears roughly in 10% of the cases
-    let new_number: BoardValue;
     if random_number < 0.9 {
-        new_number = TWO;
+        TWO
     } else {
-        new_number = FOUR;
+        FOUR
     }
-    new_number
 }
 
 #[cfg(test)]
@@ -88,7 +102,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -99,7 +114,8 @@ mod tests {
                 NON_EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, TWO, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -110,7 +126,8 @@ mod tests {
                 ONLY_ONE_EMPTY_CELL_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             assert_eq!(new_field, FULL_FIELD);
         }
     }
@@ -125,7 +142,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO_POINT_ONE,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -136,7 +154,8 @@ mod tests {
                 NON_EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO_POINT_ONE,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, TWO, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -151,7 +170,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO_POINT_EIGHT,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -167,7 +187,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO_POINT_NINE,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO,
-            );
+            )
+            .unwrap();
             let expected_field = [[FOUR, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -183,7 +204,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO_POINT_NINE_NINE,
-            );
+            )
+            .unwrap();
             let expected_field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]];
             assert_eq!(new_field, expected_field);
         }
@@ -193,7 +215,8 @@ mod tests {
                 NON_EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO_POINT_NINE_NINE,
-            );
+            )
+            .unwrap();
             let expected_field = [[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]];
             assert_eq!(new_field, expected_field);
         }
@@ -205,12 +228,71 @@ mod tests {
                 LAST_POSITION_FILLED_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO_POINT_NINE_NINE,
-            );
+            )
+            .unwrap();
             let expected_field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, TWO, TWO]];
             assert_eq!(new_field, expected_field);
         }
     }
 
+    #[cfg(test)]
+    mod given_full_field {
+        use crate::field_add_random_value::tests::*;
+        #[test]
+        fn it_should_return_an_error() {
+            assert_eq!(
+                add_value(
+                    FULL_FIELD,
+                    FAKE_RANDOM_NUMBER_VALUE_ZERO,
+                    FAKE_RANDOM_NUMBER_POSITION_ZERO
+                ),
+                Err(BoardError::Full)
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod get_index_for_coordinate {
+        use crate::field_add_random_value::tests::*;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn it_should_return_the_first_index_for_zero() {
+            assert_eq!(get_index_for_coordinate(0.0, 5), 0);
+        }
+
+        #[test]
+        fn it_should_return_the_last_index_for_the_largest_random_number() {
+            let largest_below_one = 1.0 - f32::EPSILON / 2.0;
+            assert_eq!(get_index_for_coordinate(largest_below_one, 5), 4);
+            assert_eq!(get_index_for_coordinate(largest_below_one, 16), 15);
+        }
+
+        #[test]
+        fn it_should_select_every_index_equally_often() {
+            const DRAWS: usize = 160_000;
+            for free_coordinate_count in [1, 2, 3, 7, 16] {
+                let mut rng = StdRng::seed_from_u64(free_coordinate_count as u64);
+                let mut frequencies = vec![0usize; free_coordinate_count];
+                for _ in 0..DRAWS {
+                    frequencies[get_index_for_coordinate(rng.gen(), free_coordinate_count)] += 1;
+                }
+
+                let expected = (DRAWS / free_coordinate_count) as f64;
+                for frequency in frequencies {
+                    let deviation = (frequency as f64 - expected).abs() / expected;
+                    assert!(
+                        deviation < 0.03,
+                        "{:?} deviates by {}",
+                        frequency,
+                        deviation
+                    );
+                }
+            }
+        }
+    }
+
     #[cfg(test)]
     mod given_random_number_position_zero_point_five_four {
         use crate::field_add_random_value::tests::*;
@@ -221,7 +303,8 @@ mod tests {
                 EMPTY_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO_POINT_FIVE,
-            );
+            )
+            .unwrap();
             let expected_field = [[X, X, X, X], [X, X, X, X], [TWO, X, X, X], [X, X, X, X]];
             assert_eq!(new_field, expected_field);
         }
@@ -231,7 +314,8 @@ mod tests {
                 ONLY_ONE_EMPTY_CELL_FIELD,
                 FAKE_RANDOM_NUMBER_VALUE_ZERO,
                 FAKE_RANDOM_NUMBER_POSITION_ZERO_POINT_FIVE,
-            );
+            )
+            .unwrap();
             assert_eq!(new_field, FULL_FIELD);
         }
     }
@@ -1,28 +1,46 @@
 use crate::board_value::BoardValue;
+use crate::direction::Direction;
 use crate::field::Field;
+use crate::field_move_and_merge::move_and_merge;
 
-pub fn is_game_over(field: Field) -> bool {
+pub fn is_game_over<const W: usize, const H: usize>(field: Field<W, H>) -> bool {
     let tuples = get_all_cell_neighbours(field);
     for (current, neighbour) in tuples.iter() {
-        if current == &BoardValue::new(0) {
+        if current == &BoardValue::new(0) || neighbour == &BoardValue::new(0) {
             return false;
         }
-        if current == neighbour {
+        if current == neighbour && current.duplicate().is_ok() {
             return false;
         }
     }
     true
 }
 
-fn get_all_cell_neighbours(field: Field) -> Vec<(BoardValue, BoardValue)> {
+/// Whether a move in the direction changes the field.
+pub fn can_move<const W: usize, const H: usize>(field: Field<W, H>, direction: Direction) -> bool {
+    move_and_merge(field, direction) != field
+}
+
+/// All directions which change the field. Empty exactly if the game is over.
+pub fn available_moves<const W: usize, const H: usize>(
+    field: Field<W, H>,
+) -> impl Iterator<Item = Direction> {
+    Direction::ALL
+        .into_iter()
+        .filter(move |direction| can_move(field, *direction))
+}
+
+fn get_all_cell_neighbours<const W: usize, const H: usize>(
+    field: Field<W, H>,
+) -> Vec<(BoardValue, BoardValue)> {
     let mut tuples: Vec<(BoardValue, BoardValue)> = vec![];
-    for row in 0..4 {
-        for column in 0..4 {
+    for row in 0..H {
+        for column in 0..W {
             let current = field[row][column];
-            if row < 3 {
+            if row + 1 < H {
                 tuples.push((current, field[row + 1][column]));
             }
-            if column < 3 {
+            if column + 1 < W {
                 tuples.push((current, field[row][column + 1]));
             }
         }
@@ -55,7 +73,7 @@ mod test {
             [FOUR, TWO, FOUR, TWO],
             [TWO, FOUR, TWO, FOUR],
         ];
-        assert_eq!(true, is_game_over(FULL_FIELD));
+        assert!(is_game_over(FULL_FIELD));
     }
 
     #[test]
@@ -66,7 +84,7 @@ mod test {
             [FOUR, TWO, FOUR, TWO],
             [X, X, X, X],
         ];
-        assert_eq!(false, is_game_over(LAST_ROW_EMPTY));
+        assert!(!is_game_over(LAST_ROW_EMPTY));
     }
 
     #[test]
@@ -77,7 +95,7 @@ mod test {
             [TWO, FOUR, TWO, FOUR],
             [FOUR, TWO, FOUR, TWO],
         ];
-        assert_eq!(false, is_game_over(FIRST_ROW_EMPTY));
+        assert!(!is_game_over(FIRST_ROW_EMPTY));
     }
 
     #[test]
@@ -88,7 +106,7 @@ mod test {
             [X, FOUR, TWO, FOUR],
             [X, TWO, FOUR, TWO],
         ];
-        assert_eq!(false, is_game_over(LEFT_COLUMN_EMPTY));
+        assert!(!is_game_over(LEFT_COLUMN_EMPTY));
     }
 
     #[test]
@@ -99,7 +117,7 @@ mod test {
             [TWO, FOUR, TWO, X],
             [FOUR, TWO, FOUR, X],
         ];
-        assert_eq!(false, is_game_over(RIGHT_COLUMN_EMPTY));
+        assert!(!is_game_over(RIGHT_COLUMN_EMPTY));
     }
 
     #[test]
@@ -110,7 +128,7 @@ mod test {
             [TWO, FOUR, TWO, FOUR],
             [FOUR, TWO, FOUR, TWO],
         ];
-        assert_eq!(false, is_game_over(FULL_BUT_MERGEABLE));
+        assert!(!is_game_over(FULL_BUT_MERGEABLE));
     }
 
     #[test]
@@ -121,7 +139,50 @@ mod test {
             [TWO, FOUR, TWO, FOUR],
             [FOUR, TWO, FOUR, TWO],
         ];
-        assert_eq!(false, is_game_over(ONE_FIELD_ZERO));
+        assert!(!is_game_over(ONE_FIELD_ZERO));
+    }
+
+    #[test]
+    fn it_should_return_only_directions_which_change_the_field() {
+        const TOP_LEFT: Field = [[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
+        assert_eq!(
+            available_moves(TOP_LEFT).collect::<Vec<Direction>>(),
+            vec![Direction::Right, Direction::Down]
+        );
+        assert!(!can_move(TOP_LEFT, Direction::Up));
+        assert!(can_move(TOP_LEFT, Direction::Down));
+    }
+
+    #[test]
+    fn it_should_return_directions_which_only_merge() {
+        const FULL_BUT_MERGEABLE: Field = [
+            [TWO, FOUR, EIGHT, EIGHT],
+            [FOUR, TWO, FOUR, TWO],
+            [TWO, FOUR, TWO, FOUR],
+            [FOUR, TWO, FOUR, TWO],
+        ];
+        assert_eq!(
+            available_moves(FULL_BUT_MERGEABLE).collect::<Vec<Direction>>(),
+            vec![Direction::Right, Direction::Left]
+        );
+    }
+
+    #[test]
+    fn it_should_be_game_over_exactly_if_no_move_is_available() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let values = [X, TWO, FOUR, EIGHT];
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..10_000 {
+            let field: Field = [[X; 4]; 4].map(|row| row.map(|_| values[rng.gen_range(0..4)]));
+            assert_eq!(
+                is_game_over(field),
+                available_moves(field).next().is_none(),
+                "{:?}",
+                field
+            );
+        }
     }
 
     #[test]
@@ -134,6 +195,39 @@ mod test {
             [TWO, FOUR, TWO, FOUR],
             [FOUR, TWO, FOUR, TWO],
         ];
-        assert_eq!(false, is_game_over(FULL_BUT_MERGEABLE));
+        assert!(!is_game_over(FULL_BUT_MERGEABLE));
+    }
+
+    #[test]
+    fn it_should_detect_the_end_of_a_3x3_game() {
+        assert!(is_game_over([
+            [TWO, FOUR, TWO],
+            [FOUR, TWO, FOUR],
+            [TWO, FOUR, TWO]
+        ]));
+        assert!(!is_game_over([
+            [TWO, FOUR, TWO],
+            [FOUR, TWO, FOUR],
+            [TWO, FOUR, FOUR]
+        ]));
+    }
+
+    #[test]
+    fn it_should_compare_the_neighbours_of_a_rectangular_field() {
+        let tall: Field<2, 3> = [[TWO, FOUR], [FOUR, TWO], [TWO, FOUR]];
+        assert_eq!(get_all_cell_neighbours(tall).len(), 7);
+        assert!(is_game_over(tall));
+        assert!(!is_game_over([[TWO, FOUR], [FOUR, TWO], [FOUR, EIGHT]]));
+    }
+
+    #[test]
+    fn it_should_return_false_if_only_the_last_cell_is_empty() {
+        const LAST_CELL_EMPTY: Field = [
+            [FOUR, TWO, FOUR, TWO],
+            [TWO, FOUR, TWO, FOUR],
+            [FOUR, TWO, FOUR, TWO],
+            [TWO, FOUR, TWO, X],
+        ];
+        assert!(!is_game_over(LAST_CELL_EMPTY));
     }
 }
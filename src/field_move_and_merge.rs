@@ -1,61 +1,226 @@
+use crate::board::Coordinate;
+use crate::direction::Direction;
 use crate::field::Field;
 use crate::field::Row;
 use crate::board_value::BoardValue;
-use crate::field_rotate::*;
-
-pub fn move_and_merge_left(field: Field) -> Field {
-    [
-        move_and_merge_row_left(field[0]),
-        move_and_merge_row_left(field[1]),
-        move_and_merge_row_left(field[2]),
-        move_and_merge_row_left(field[3]),
-    ]
+use crate::move_outcome::Merge;
+
+pub fn move_and_merge<const W: usize, const H: usize>(
+    field: Field<W, H>,
+    direction: Direction,
+) -> Field<W, H> {
+    match direction {
+        Direction::Up => move_and_merge_up(field),
+        Direction::Right => move_and_merge_right(field),
+        Direction::Down => move_and_merge_down(field),
+        Direction::Left => move_and_merge_left(field),
+    }
 }
 
-pub fn move_and_merge_up(field: Field) -> Field {
-    rotate_clockwise(move_and_merge_left(rotate_counterclockwise(field)))
+pub fn move_and_merge_left<const W: usize, const H: usize>(field: Field<W, H>) -> Field<W, H> {
+    field.map(move_and_merge_row_left)
 }
 
-pub fn move_and_merge_down(field: Field) -> Field {
-    rotate_counterclockwise(move_and_merge_left(rotate_clockwise(field)))
+pub fn move_and_merge_up<const W: usize, const H: usize>(field: Field<W, H>) -> Field<W, H> {
+    move_and_merge_traced(field, Direction::Up, false).0
 }
 
-pub fn move_and_merge_right(field: Field) -> Field {
-    rotate_clockwise(move_and_merge_up(rotate_counterclockwise(field)))
+pub fn move_and_merge_down<const W: usize, const H: usize>(field: Field<W, H>) -> Field<W, H> {
+    move_and_merge_traced(field, Direction::Down, false).0
 }
 
-fn move_and_merge_row_left(row: Row) -> Row {
-    let mut result: Row = row; // Implicit clone
+pub fn move_and_merge_right<const W: usize, const H: usize>(field: Field<W, H>) -> Field<W, H> {
+    move_and_merge_traced(field, Direction::Right, false).0
+}
 
-    for index in 0..3 {
-        result = pull(result, index);
-        result = merge(result, index);
+/// Moves the field and returns every merge of the move with the cells of
+/// the merged tiles, and every step of the move in the order it was made.
+/// With `chain_merges` a merged tile may merge again.
+pub fn move_and_merge_traced<const W: usize, const H: usize>(
+    field: Field<W, H>,
+    direction: Direction,
+    chain_merges: bool,
+) -> (Field<W, H>, Vec<Merge>, Vec<CellEvent>) {
+    match direction {
+        Direction::Left | Direction::Right => {
+            move_and_merge_lines_traced::<W, H, W>(field, direction, chain_merges)
+        }
+        Direction::Up | Direction::Down => {
+            move_and_merge_lines_traced::<W, H, H>(field, direction, chain_merges)
+        }
     }
-    result
 }
 
-fn pull(row: Row, index: usize) -> Row {
+/// Moves every line of length `L` to its start: the rows for a horizontal
+/// and the columns for a vertical move.
+fn move_and_merge_lines_traced<const W: usize, const H: usize, const L: usize>(
+    field: Field<W, H>,
+    direction: Direction,
+    chain_merges: bool,
+) -> (Field<W, H>, Vec<Merge>, Vec<CellEvent>) {
+    let to_coordinate = get_coordinate_function::<W, H>(direction);
+    let mut result = field;
+    let mut merges: Vec<Merge> = Vec::new();
+    let mut events: Vec<CellEvent> = Vec::new();
+    for line in 0..get_line_count::<W, H>(direction) {
+        let (moved, trace) = move_and_merge_row_left_with_trace(
+            extract_line::<W, H, L>(field, line, to_coordinate),
+            chain_merges,
+        );
+        for (position, value) in moved.into_iter().enumerate() {
+            let (row, column) = to_coordinate(line, position);
+            result[row][column] = value;
+        }
+        events.extend(trace.events.into_iter().map(|row_event| match row_event {
+            RowEvent::Moved { from, to } => CellEvent::Moved {
+                from: to_coordinate(line, from),
+                to: to_coordinate(line, to),
+            },
+            RowEvent::Merged {
+                sources,
+                into,
+                value,
+            } => CellEvent::Merged {
+                sources: sources.map(|position| to_coordinate(line, position)),
+                into: to_coordinate(line, into),
+                value,
+            },
+        }));
+        merges.extend(trace.merges.into_iter().map(|row_merge| {
+            Merge {
+                sources: row_merge
+                    .sources
+                    .map(|position| to_coordinate(line, position)),
+                target: to_coordinate(line, row_merge.target),
+                value: row_merge.value.value(),
+            }
+        }));
+    }
+    (result, merges, events)
+}
+
+/// A merge within a single row, as indices into the row before the move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RowMerge {
+    pub sources: [usize; 2],
+    pub target: usize,
+    pub value: BoardValue,
+}
+
+/// A single step of a row move, as indices into the row at the time of the
+/// step.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RowEvent {
+    Moved {
+        from: usize,
+        to: usize,
+    },
+    Merged {
+        sources: [usize; 2],
+        into: usize,
+        value: BoardValue,
+    },
+}
+
+/// A single step of a move, as cells on the field at the time of the step.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CellEvent {
+    Moved {
+        from: Coordinate,
+        to: Coordinate,
+    },
+    Merged {
+        sources: [Coordinate; 2],
+        into: Coordinate,
+        value: BoardValue,
+    },
+}
+
+/// A row together with the original index of every value in it.
+#[derive(Clone, Copy)]
+struct TracedRow<const N: usize> {
+    values: Row<N>,
+    origins: [usize; N],
+}
+
+#[derive(Default)]
+struct RowTrace {
+    merges: Vec<RowMerge>,
+    events: Vec<RowEvent>,
+}
+
+fn move_and_merge_row_left<const N: usize>(row: Row<N>) -> Row<N> {
+    move_and_merge_row_left_with_trace(row, false).0
+}
+
+fn move_and_merge_row_left_with_trace<const N: usize>(
+    row: Row<N>,
+    chain_merges: bool,
+) -> (Row<N>, RowTrace) {
+    let mut result = TracedRow {
+        values: row, // Implicit clone
+        origins: std::array::from_fn(|index| index),
+    };
+    let mut trace = RowTrace::default();
+
+    let mut index = 0;
+    while index < N.saturating_sub(1) {
+        result = pull(result, index, &mut trace);
+        let merge_count = trace.merges.len();
+        result = merge(result, index, &mut trace);
+        // A chained merge tries the merged tile against the next one again
+        if !chain_merges || trace.merges.len() == merge_count {
+            index += 1;
+        }
+    }
+    (result.values, trace)
+}
+
+fn pull<const N: usize>(row: TracedRow<N>, index: usize, trace: &mut RowTrace) -> TracedRow<N> {
     let mut result = row;
-    let next_value = find_next_value(result, index + 1);
-    if result[index] == BoardValue::new(0) {
-        result[index] = result[next_value];
-        result[next_value] = BoardValue::new(0);
+    let next_value = find_next_value(result.values, index + 1);
+    if result.values[index] == BoardValue::new(0) {
+        if result.values[next_value] != BoardValue::new(0) {
+            trace.events.push(RowEvent::Moved {
+                from: next_value,
+                to: index,
+            });
+        }
+        result.values[index] = result.values[next_value];
+        result.values[next_value] = BoardValue::new(0);
+        result.origins[index] = result.origins[next_value];
     }
     result
 }
 
-fn merge(row: Row, index: usize) -> Row {
+fn merge<const N: usize>(row: TracedRow<N>, index: usize, trace: &mut RowTrace) -> TracedRow<N> {
     let mut result = row;
-    let next_value = find_next_value(result, index + 1);
-    if result[index] == result[next_value] {
-        result[index] = result[index].duplicate();
-        result[next_value] = BoardValue::new(0);
-        result = shift_values(result, index + 1);
+    let next_value = find_next_value(result.values, index + 1);
+    if result.values[index] == result.values[next_value] {
+        // Two tiles of the largest value stay apart
+        let Ok(merged) = result.values[index].duplicate() else {
+            return result;
+        };
+        result.values[index] = merged;
+        result.values[next_value] = BoardValue::new(0);
+        if result.values[index] != BoardValue::new(0) {
+            trace.merges.push(RowMerge {
+                sources: [result.origins[index], result.origins[next_value]],
+                target: index,
+                value: result.values[index],
+            });
+            trace.events.push(RowEvent::Merged {
+                sources: [index, next_value],
+                into: index,
+                value: result.values[index],
+            });
+        }
+        result = shift_values(result, index + 1, trace);
     }
     result
 }
 
-fn find_next_value(row: Row, start_index: usize) -> usize {
+fn find_next_value<const N: usize>(row: Row<N>, start_index: usize) -> usize {
     let mut result: usize = start_index;
     for (index, cell) in row.iter().enumerate().skip(start_index) {
         if *cell != BoardValue::new(0) {
@@ -66,16 +231,60 @@ fn find_next_value(row: Row, start_index: usize) -> usize {
     result
 }
 
-fn shift_values(row: Row, start_index: usize) -> Row {
-    let mut result: Row = row;
-    for index in start_index..=2 {
+fn shift_values<const N: usize>(
+    row: TracedRow<N>,
+    start_index: usize,
+    trace: &mut RowTrace,
+) -> TracedRow<N> {
+    let mut result: TracedRow<N> = row;
+    for index in start_index..N - 1 {
         let source_index = index + 1;
-        result[index] = result[source_index];
-        result[source_index] = BoardValue::new(0);
+        if result.values[source_index] != BoardValue::new(0) {
+            trace.events.push(RowEvent::Moved {
+                from: source_index,
+                to: index,
+            });
+        }
+        result.values[index] = result.values[source_index];
+        result.values[source_index] = BoardValue::new(0);
+        result.origins[index] = result.origins[source_index];
     }
     result
 }
 
+/// The number of lines which move independently: the rows for a horizontal
+/// and the columns for a vertical move.
+fn get_line_count<const W: usize, const H: usize>(direction: Direction) -> usize {
+    match direction {
+        Direction::Left | Direction::Right => H,
+        Direction::Up | Direction::Down => W,
+    }
+}
+
+/// Returns a function which maps a position within a line, counted in the
+/// direction of the move, to the cell on the field.
+fn get_coordinate_function<const W: usize, const H: usize>(
+    direction: Direction,
+) -> fn(usize, usize) -> Coordinate {
+    match direction {
+        Direction::Up => |line, position| (position, line),
+        Direction::Right => |line, position| (line, W - 1 - position),
+        Direction::Down => |line, position| (H - 1 - position, line),
+        Direction::Left => |line, position| (line, position),
+    }
+}
+
+fn extract_line<const W: usize, const H: usize, const L: usize>(
+    field: Field<W, H>,
+    line: usize,
+    to_coordinate: fn(usize, usize) -> Coordinate,
+) -> Row<L> {
+    std::array::from_fn(|position| {
+        let (row, column) = to_coordinate(line, position);
+        field[row][column]
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::field::tests::*;
@@ -244,8 +453,45 @@ mod tests {
     #[cfg(test)]
     mod tests_move_and_merge_row_left {
 
-        use crate::field_move_and_merge::move_and_merge_row_left;
         use crate::field_move_and_merge::tests::*;
+        use crate::field_move_and_merge::{
+            move_and_merge_row_left, move_and_merge_row_left_with_trace, Row, RowEvent, RowMerge,
+        };
+        use crate::board_value::{BoardValue, MAX_EXPONENT};
+
+        fn move_and_merge_row_left_traced(
+            row: Row<4>,
+            chain_merges: bool,
+        ) -> (Row<4>, Vec<RowMerge>) {
+            let (row, trace) = move_and_merge_row_left_with_trace(row, chain_merges);
+            (row, trace.merges)
+        }
+
+        fn move_and_merge_row_left_with_events(
+            row: Row<4>,
+            chain_merges: bool,
+        ) -> (Row<4>, Vec<RowEvent>) {
+            let (row, trace) = move_and_merge_row_left_with_trace(row, chain_merges);
+            (row, trace.events)
+        }
+
+        #[test]
+        fn it_should_merge_values_beyond_32_bits() {
+            let large = BoardValue::new(1 << 32);
+            assert_eq!(
+                move_and_merge_row_left([large, large, X, X]),
+                [BoardValue::new(1 << 33), X, X, X]
+            );
+        }
+
+        #[test]
+        fn it_should_not_merge_the_largest_values() {
+            let largest = BoardValue::from_exponent(MAX_EXPONENT).unwrap();
+            assert_eq!(
+                move_and_merge_row_left([X, largest, largest, TWO]),
+                [largest, largest, TWO, X]
+            );
+        }
 
         #[test]
         fn it_should_do_nothing_on_an_empty_row() {
@@ -344,6 +590,114 @@ mod tests {
             );
         }
 
+        // traced merges
+        #[test]
+        fn it_should_trace_the_original_indices_of_merged_values() {
+            let (row, merges) = move_and_merge_row_left_traced([TWO, TWO, FOUR, FOUR], false);
+
+            assert_eq!(row, [FOUR, EIGHT, X, X]);
+            assert_eq!(
+                merges,
+                vec![
+                    RowMerge {
+                        sources: [0, 1],
+                        target: 0,
+                        value: FOUR
+                    },
+                    RowMerge {
+                        sources: [2, 3],
+                        target: 1,
+                        value: EIGHT
+                    }
+                ]
+            );
+        }
+
+        #[test]
+        fn it_should_trace_merged_values_which_were_moved() {
+            let (_, merges) = move_and_merge_row_left_traced([X, FOUR, TWO, TWO], false);
+
+            assert_eq!(
+                merges,
+                vec![RowMerge {
+                    sources: [2, 3],
+                    target: 1,
+                    value: FOUR
+                }]
+            );
+        }
+
+        #[test]
+        fn it_should_not_trace_merges_when_nothing_merged() {
+            let (_, merges) = move_and_merge_row_left_traced([X, TWO, X, FOUR], false);
+            assert!(merges.is_empty());
+        }
+
+        // chained merges
+        #[test]
+        fn it_should_merge_a_merged_value_again_with_chain_merges() {
+            let (row, merges) = move_and_merge_row_left_traced([TWO, TWO, FOUR, EIGHT], true);
+
+            assert_eq!(row, [SIXTEEN, X, X, X]);
+            assert_eq!(
+                merges.iter().map(|merge| merge.value).collect::<Vec<_>>(),
+                vec![FOUR, EIGHT, SIXTEEN]
+            );
+        }
+
+        #[test]
+        fn it_should_continue_after_a_chain_of_merges() {
+            let (row, _) = move_and_merge_row_left_traced([TWO, TWO, FOUR, TWO], true);
+            assert_eq!(row, [EIGHT, TWO, X, X]);
+        }
+
+        #[test]
+        fn it_should_not_merge_backwards_with_chain_merges() {
+            let (row, _) = move_and_merge_row_left_traced([FOUR, TWO, TWO, X], true);
+            assert_eq!(row, [FOUR, FOUR, X, X]);
+        }
+
+        // events
+        #[test]
+        fn it_should_report_the_steps_of_the_move() {
+            let (_, events) = move_and_merge_row_left_with_events([X, TWO, X, TWO], false);
+
+            assert_eq!(
+                events,
+                vec![
+                    RowEvent::Moved { from: 1, to: 0 },
+                    RowEvent::Merged {
+                        sources: [0, 3],
+                        into: 0,
+                        value: FOUR
+                    }
+                ]
+            );
+        }
+
+        #[test]
+        fn it_should_report_values_shifted_after_a_merge() {
+            let (_, events) = move_and_merge_row_left_with_events([TWO, TWO, FOUR, X], false);
+
+            assert_eq!(
+                events,
+                vec![
+                    RowEvent::Merged {
+                        sources: [0, 1],
+                        into: 0,
+                        value: FOUR
+                    },
+                    RowEvent::Moved { from: 2, to: 1 }
+                ]
+            );
+        }
+
+        #[test]
+        fn it_should_report_nothing_if_nothing_changed() {
+            let (_, events) = move_and_merge_row_left_with_events([TWO, FOUR, X, X], false);
+            assert!(events.is_empty());
+        }
+
         // move and merge
         #[test]
         fn it_should_merge_and_move_two_same_values() {
@@ -377,4 +731,139 @@ mod tests {
             );
         }
     }
+
+    #[cfg(test)]
+    mod other_sizes {
+        use crate::direction::Direction;
+        use crate::field_move_and_merge::tests::*;
+        use crate::field_move_and_merge::{move_and_merge, move_and_merge_traced};
+        use crate::move_outcome::Merge;
+
+        #[test]
+        fn it_should_move_and_merge_a_3x3_field_in_every_direction() {
+            let field = [[TWO, TWO, X], [X, X, X], [TWO, X, FOUR]];
+
+            assert_eq!(
+                move_and_merge(field, Direction::Left),
+                [[FOUR, X, X], [X, X, X], [TWO, FOUR, X]]
+            );
+            assert_eq!(
+                move_and_merge(field, Direction::Right),
+                [[X, X, FOUR], [X, X, X], [X, TWO, FOUR]]
+            );
+            assert_eq!(
+                move_and_merge(field, Direction::Up),
+                [[FOUR, TWO, FOUR], [X, X, X], [X, X, X]]
+            );
+            assert_eq!(
+                move_and_merge(field, Direction::Down),
+                [[X, X, X], [X, X, X], [FOUR, TWO, FOUR]]
+            );
+        }
+
+        #[test]
+        fn it_should_merge_every_pair_of_a_long_row() {
+            let field = [
+                [TWO, TWO, TWO, TWO, TWO],
+                [X, X, X, X, X],
+                [X, X, X, X, X],
+                [X, X, X, X, X],
+                [X, X, X, X, EIGHT],
+            ];
+
+            assert_eq!(
+                move_and_merge(field, Direction::Right),
+                [
+                    [X, X, TWO, FOUR, FOUR],
+                    [X, X, X, X, X],
+                    [X, X, X, X, X],
+                    [X, X, X, X, X],
+                    [X, X, X, X, EIGHT],
+                ]
+            );
+        }
+
+        #[test]
+        fn it_should_report_merges_with_cells_of_a_larger_field() {
+            let mut field = [[X; 6]; 6];
+            field[0][5] = TWO;
+            field[5][5] = TWO;
+
+            assert_eq!(
+                move_and_merge_traced(field, Direction::Down, false).1,
+                vec![Merge {
+                    sources: [(5, 5), (0, 5)],
+                    target: (5, 5),
+                    value: 4
+                }]
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod rectangular {
+        use crate::direction::Direction;
+        use crate::field::Field;
+        use crate::field_move_and_merge::tests::*;
+        use crate::field_move_and_merge::{move_and_merge, move_and_merge_traced, CellEvent};
+
+        // Two columns, three rows
+        const TALL: Field<2, 3> = [[TWO, X], [X, FOUR], [TWO, FOUR]];
+
+        #[test]
+        fn it_should_move_a_tall_field_vertically() {
+            assert_eq!(
+                move_and_merge(TALL, Direction::Up),
+                [[FOUR, EIGHT], [X, X], [X, X]]
+            );
+            assert_eq!(
+                move_and_merge(TALL, Direction::Down),
+                [[X, X], [X, X], [FOUR, EIGHT]]
+            );
+        }
+
+        #[test]
+        fn it_should_move_a_tall_field_horizontally() {
+            assert_eq!(
+                move_and_merge(TALL, Direction::Left),
+                [[TWO, X], [FOUR, X], [TWO, FOUR]]
+            );
+            assert_eq!(
+                move_and_merge(TALL, Direction::Right),
+                [[X, TWO], [X, FOUR], [TWO, FOUR]]
+            );
+        }
+
+        #[test]
+        fn it_should_move_a_wide_field() {
+            let wide: Field<8, 2> = [[TWO, X, X, X, X, X, X, TWO], [X, X, X, X, X, X, X, EIGHT]];
+            assert_eq!(
+                move_and_merge(wide, Direction::Right),
+                [[X, X, X, X, X, X, X, FOUR], [X, X, X, X, X, X, X, EIGHT]]
+            );
+            assert_eq!(
+                move_and_merge(wide, Direction::Up),
+                [[TWO, X, X, X, X, X, X, TWO], [X, X, X, X, X, X, X, EIGHT]]
+            );
+        }
+
+        #[test]
+        fn it_should_report_the_cells_of_a_vertical_move() {
+            assert_eq!(
+                move_and_merge_traced(TALL, Direction::Down, false).2,
+                vec![
+                    CellEvent::Merged {
+                        sources: [(2, 0), (0, 0)],
+                        into: (2, 0),
+                        value: FOUR
+                    },
+                    CellEvent::Merged {
+                        sources: [(2, 1), (1, 1)],
+                        into: (2, 1),
+                        value: EIGHT
+                    },
+                ]
+            );
+        }
+    }
 }
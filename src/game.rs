@@ -1,86 +1,377 @@
-use crate::score_calculator::Score;
+use crate::board::Board;
 use crate::board_value::BoardValue;
+use crate::direction::Direction;
 use crate::field::Field;
-use crate::field_gameover::is_game_over;
+use crate::field_gameover::{available_moves, can_move, is_game_over};
+use crate::field_move_and_merge::*;
 use crate::game_actions::*;
-use crate::random::random;
+use crate::game_status::{GameError, GameStatus};
+use crate::history::{History, HistoryDepth};
+use crate::move_outcome::MoveOutcome;
+use crate::rules::Rules;
+use crate::score_calculator::{calculate_points, Score};
+use crate::tile_spawner::TileSpawner;
+use crate::tile_tracking::{TileIds, TileTracker};
+use rand::{Rng, SeedableRng};
 
-pub struct Game {
-    field: Field,
+/// The random number generator used by [`Game::new`] and [`Game::with_seed`].
+pub type GameRng = rand_chacha::ChaCha8Rng;
+
+/// A game on a board of `W` columns and `H` rows, 4x4 unless specified
+/// otherwise.
+///
+/// [`Game::new`], [`Game::with_seed`] and [`Game::from_field`] create the
+/// classic 4x4 game. Other sizes are created with [`Game::with_rng`],
+/// [`Game::with_rules_and_rng`] or [`Game::from_field_with_rng`], e.g.
+/// `Game::<GameRng, 3>::with_rng(GameRng::seed_from_u64(7))` for a 3x3 or
+/// `Game::<GameRng, 4, 6>::with_rng(...)` for a board four columns wide and
+/// six rows tall.
+pub struct Game<R = GameRng, const W: usize = 4, const H: usize = W> {
+    field: Field<W, H>,
+    score: Score,
+    moves: u64,
+    rng: R,
+    spawner: Box<dyn TileSpawner<W, H> + Send>,
+    tracker: Option<TileTracker<W, H>>,
+    rules: Rules,
+    status: GameStatus,
+    history: History<Snapshot<R, W, H>>,
+    undo_tokens: Option<u32>,
+}
+
+/// Everything a move changes, so that undo and redo can restore it.
+struct Snapshot<R, const W: usize, const H: usize> {
+    field: Field<W, H>,
     score: Score,
+    moves: u64,
+    rng: R,
+    status: GameStatus,
+    tracker: Option<TileTracker<W, H>>,
+}
+
+/// The cells of a board in row-major order together with the width of a row.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExternalFieldRepresentation {
+    pub width: usize,
+    pub values: Vec<u64>,
 }
 
-pub type ExternalFieldRepresentation = Vec<u32>;
+impl ExternalFieldRepresentation {
+    pub fn height(&self) -> usize {
+        self.values.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u64]> {
+        self.values.chunks(self.width.max(1))
+    }
+}
 pub type ExternalScore = u64;
 
 impl Game {
     pub fn new() -> Self {
-        const X: BoardValue = BoardValue::new(0);
-        const EMPTY_FIELD: Field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]];
-        let initialized_field = initialize_field(EMPTY_FIELD, random(), random());
-        Self {
-            field: initialized_field,
-            score: 0,
-        }
+        Self::with_rng(GameRng::from_entropy())
+    }
+
+    /// Creates a reproducible game: the same seed and the same moves always
+    /// result in the same fields and score.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(GameRng::seed_from_u64(seed))
+    }
+
+    /// Creates a game which follows the given rules from its first tile.
+    pub fn with_rules(rules: Rules) -> Self {
+        Self::with_rules_and_rng(rules, GameRng::from_entropy())
+    }
+
+    pub fn from_field(board: impl Into<Board>) -> Self {
+        Self::from_field_with_rng(board, GameRng::from_entropy())
+    }
+}
+
+impl<R: Rng + Clone, const W: usize, const H: usize> Game<R, W, H> {
+    /// Creates a game which uses the given random number generator for the
+    /// value and the position of every new tile.
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_rules_and_rng(Rules::classic(), rng)
+    }
+
+    /// Places the starting tiles of the rules with the given random number
+    /// generator.
+    pub fn with_rules_and_rng(rules: Rules, mut rng: R) -> Self {
+        let empty_field: Field<W, H> = [[BoardValue::EMPTY; W]; H];
+        let initialized_field = initialize_field(empty_field, &rules, &mut rng);
+        Self::from_field_with_rules_and_rng(Board::from(initialized_field), rules, rng)
+    }
+
+    pub fn from_field_with_rng(board: impl Into<Board<W, H>>, rng: R) -> Self {
+        Self::from_field_with_rules_and_rng(board, Rules::classic(), rng)
     }
 
-    pub fn from_field(field: Field) -> Self {
+    pub fn from_field_with_rules_and_rng(
+        board: impl Into<Board<W, H>>,
+        rules: Rules,
+        rng: R,
+    ) -> Self {
+        let field = board.into().field();
         Self {
             field,
             score: 0,
+            moves: 0,
+            rng,
+            spawner: Box::new(rules.spawner()),
+            tracker: None,
+            rules,
+            status: if is_game_over(field) {
+                GameStatus::Lost
+            } else {
+                GameStatus::Playing
+            },
+            history: History::new(HistoryDepth::Limited(0)),
+            undo_tokens: None,
         }
     }
 
+    /// Replaces the spawner which places the new tiles after every move. The
+    /// spawn weights of the rules no longer apply then. The spawner has to be
+    /// `Send`, so that games can be moved to other threads.
+    pub fn with_spawner(mut self, spawner: impl TileSpawner<W, H> + Send + 'static) -> Self {
+        self.spawner = Box::new(spawner);
+        self
+    }
+
+    /// Sets the tile value which wins the game, 2048 by default.
+    pub fn with_target(mut self, target: u64) -> Self {
+        self.rules = self.rules.with_target(target);
+        self
+    }
+
+    /// Keeps the last moves for [`Game::undo`] and [`Game::redo`]. Without
+    /// it no move can be undone.
+    ///
+    /// The state of the spawner is not part of the history, so a spawner
+    /// like [`ScriptedSpawner`](crate::tile_spawner::ScriptedSpawner) does
+    /// not rewind on undo.
+    pub fn with_history(mut self, depth: HistoryDepth) -> Self {
+        self.history = History::new(depth);
+        self
+    }
+
+    /// Allows only the given number of undos for the whole game. Redos are
+    /// free.
+    pub fn with_undo_tokens(mut self, tokens: u32) -> Self {
+        self.undo_tokens = Some(tokens);
+        self
+    }
+
+    /// Gives every tile an id and reports the movement of the tiles in the
+    /// [`MoveOutcome::events`] of every move, e.g. for animations.
+    pub fn with_tile_tracking(mut self) -> Self {
+        self.tracker = Some(TileTracker::new(self.field));
+        self
+    }
+
+    /// The id of the tile in every cell, if tile tracking is enabled.
+    pub fn get_tile_ids(&self) -> Option<TileIds<W, H>> {
+        self.tracker.as_ref().map(TileTracker::get_ids)
+    }
+
     pub fn get_score(&self) -> ExternalScore {
         self.score
     }
 
+    /// The number of moves which changed the board.
+    pub fn get_move_count(&self) -> u64 {
+        self.moves
+    }
+
     pub fn get_field(&self) -> ExternalFieldRepresentation {
-        let field_iter = self.field.into_iter();
-        // Rust Vector<->Array madness solved with help from
-        // https://stackoverflow.com/a/63355120 (and comments)
-        field_iter
-            .map(|column| {
-                column
-                    .into_iter()
-                    .map(|cell| cell.get_value())
-                    .collect::<Vec<u32>>()
-            })
-            .collect::<Vec<Vec<u32>>>()
-            .into_iter()
-            .flatten()
-            .collect::<Vec<u32>>()
-    }
-
-    pub fn move_down(&mut self) {
-        let (field, added_points) = move_field_down(self.field, random(), random());
-        self.field = field;
-        self.score += added_points;
+        self.get_board().to_external()
+    }
+
+    pub fn get_board(&self) -> Board<W, H> {
+        Board::from(self.field)
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Keeps playing after the target was reached.
+    pub fn continue_after_win(&mut self) -> Result<(), GameError> {
+        if self.status != GameStatus::Won {
+            return Err(GameError::NotWon);
+        }
+        self.status = if is_game_over(self.field) {
+            GameStatus::Lost
+        } else {
+            GameStatus::ContinuePlaying
+        };
+        Ok(())
     }
 
-    pub fn move_right(&mut self) {
-        let (field, added_points) = move_field_right(self.field, random(), random());
+    /// Moves all tiles in the direction. Fails if the game is lost, or won
+    /// and not continued yet.
+    pub fn make_move(&mut self, direction: Direction) -> Result<MoveOutcome, GameError> {
+        match self.status {
+            GameStatus::Lost => return Err(GameError::GameOver),
+            GameStatus::Won => return Err(GameError::GameWon),
+            GameStatus::Playing | GameStatus::ContinuePlaying => {}
+        }
+        let before = self.history.is_recording().then(|| self.snapshot());
+        let (field, mut outcome, cell_events) = move_field(
+            self.field,
+            direction,
+            &self.rules,
+            self.spawner.as_mut(),
+            &mut self.rng,
+        );
+        if let Some(tracker) = self.tracker.as_mut() {
+            outcome.events = tracker.track(cell_events, &outcome.spawned);
+        }
+        if field != self.field {
+            if let Some(before) = before {
+                self.history.record(before);
+            }
+            self.moves += 1;
+        }
         self.field = field;
-        self.score += added_points;
+        self.score += outcome.points;
+        if outcome.won && self.status == GameStatus::Playing {
+            self.status = GameStatus::Won;
+        } else if outcome.lost {
+            self.status = GameStatus::Lost;
+        }
+        Ok(outcome)
+    }
+
+    /// Restores the board, the score and the random number generator before
+    /// the last move, so that repeating the move spawns the same tile.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        if !self.history.can_undo() {
+            return Err(GameError::NothingToUndo);
+        }
+        if self.undo_tokens == Some(0) {
+            return Err(GameError::NoUndoTokensLeft);
+        }
+        let current = self.snapshot();
+        if let Some(previous) = self.history.undo(current) {
+            self.restore(previous);
+            self.undo_tokens = self.undo_tokens.map(|tokens| tokens - 1);
+        }
+        Ok(())
+    }
+
+    /// Restores the state before the last undo.
+    pub fn redo(&mut self) -> Result<(), GameError> {
+        let current = self.snapshot();
+        let next = self.history.redo(current).ok_or(GameError::NothingToRedo)?;
+        self.restore(next);
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo() && self.undo_tokens != Some(0)
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// The undos left, if they are limited.
+    pub fn get_undo_tokens(&self) -> Option<u32> {
+        self.undo_tokens
+    }
+
+    /// The random number generator in its current state, e.g. for saving.
+    #[cfg(feature = "serde")]
+    pub(crate) fn rng(&self) -> &R {
+        &self.rng
+    }
+
+    /// Continues a game from a saved score, move count and status.
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_progress(mut self, score: Score, moves: u64, status: GameStatus) -> Self {
+        self.score = score;
+        self.moves = moves;
+        self.status = status;
+        self
+    }
+
+    fn snapshot(&self) -> Snapshot<R, W, H> {
+        Snapshot {
+            field: self.field,
+            score: self.score,
+            moves: self.moves,
+            rng: self.rng.clone(),
+            status: self.status,
+            tracker: self.tracker.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot<R, W, H>) {
+        self.field = snapshot.field;
+        self.score = snapshot.score;
+        self.moves = snapshot.moves;
+        self.rng = snapshot.rng;
+        self.status = snapshot.status;
+        self.tracker = snapshot.tracker;
+    }
+
+    pub fn move_down(&mut self) -> Result<MoveOutcome, GameError> {
+        self.make_move(Direction::Down)
+    }
+
+    pub fn move_right(&mut self) -> Result<MoveOutcome, GameError> {
+        self.make_move(Direction::Right)
     }
 
     pub fn is_game_over(&self) -> bool {
         is_game_over(self.field)
     }
 
-    pub fn move_up(&mut self) {
-        let (field, added_points) = move_field_up(self.field, random(), random());
-        self.field = field;
-        self.score += added_points;
+    /// The directions which would change the field. No tile is spawned to
+    /// find out. Empty exactly if [`Game::is_game_over`] is true.
+    pub fn available_moves(&self) -> impl Iterator<Item = Direction> {
+        available_moves(self.field)
     }
 
-    pub fn move_left(&mut self) {
-        let (field, added_points) = move_field_left(self.field, random(), random());
-        self.field = field;
-        self.score += added_points;
+    pub fn can_move(&self, direction: Direction) -> bool {
+        can_move(self.field, direction)
+    }
+
+    pub fn move_up(&mut self) -> Result<MoveOutcome, GameError> {
+        self.make_move(Direction::Up)
+    }
+
+    pub fn move_left(&mut self) -> Result<MoveOutcome, GameError> {
+        self.make_move(Direction::Left)
     }
 }
 
+/// Moves and merges the tiles of the board in the given direction, every tile
+/// merging at most once. No tile is spawned, so the outcome has no spawned
+/// tiles, and `won` is never set as the target depends on the rules, see
+/// [`Rules::after_state`] for other rules.
+pub fn apply_move<const W: usize, const H: usize>(
+    board: Board<W, H>,
+    direction: Direction,
+) -> (Board<W, H>, MoveOutcome) {
+    let field = board.field();
+    let (moved, merges, _) = move_and_merge_traced(field, direction, false);
+    let outcome = MoveOutcome {
+        moved: moved != field,
+        points: calculate_points(&merges),
+        merges,
+        lost: is_game_over(moved),
+        ..MoveOutcome::default()
+    };
+    (Board::from(moved), outcome)
+}
+
 impl Default for Game {
     fn default() -> Self {
         Game::new()
@@ -92,12 +383,13 @@ mod tests {
     use crate::field::tests::*;
     use crate::game::ExternalFieldRepresentation;
     use crate::game::Game;
+    use crate::rules::Rules;
 
     fn count_filled_fields(field: ExternalFieldRepresentation) -> u8 {
         let mut number = 0;
-        for x in field {
+        for x in field.values {
             if x != 0 {
-                number = number + 1
+                number += 1
             }
         }
         number
@@ -112,7 +404,7 @@ mod tests {
     #[test]
     fn it_initializes_a_non_over_game() {
         let game = Game::new();
-        assert_eq!(game.is_game_over(), false);
+        assert!(!game.is_game_over());
     }
 
     #[test]
@@ -121,19 +413,39 @@ mod tests {
         assert_eq!(game.get_score(), 0);
     }
 
+    fn new_game_with_one_tile() -> Game {
+        Game::with_rules(Rules::classic().with_starting_tiles(1))
+    }
+
     #[test]
     fn it_initializes_a_non_initial_field() {
         let game = Game::new();
         let field = game.get_field();
-        assert_eq!(count_filled_fields(field), 1);
+        assert_eq!(count_filled_fields(field), 2);
+    }
+
+    #[test]
+    fn it_initializes_the_starting_tiles_of_the_rules() {
+        let game = Game::with_rules(Rules::classic().with_starting_tiles(5));
+        assert_eq!(count_filled_fields(game.get_field()), 5);
+    }
+
+    #[test]
+    fn it_should_count_only_moves_which_changed_the_board() {
+        let mut game = Game::from_field([[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]]);
+
+        game.move_left().unwrap();
+        game.move_right().unwrap();
+
+        assert_eq!(game.get_move_count(), 1);
     }
 
     #[test]
     fn it_should_have_added_a_value_after_move_down_and_up() {
         let mut game = Game::new();
 
-        game.move_down();
-        game.move_up();
+        game.move_down().unwrap();
+        game.move_up().unwrap();
 
         assert!(count_filled_fields(game.get_field()) > 1);
     }
@@ -142,14 +454,60 @@ mod tests {
     fn it_should_have_added_a_value_after_move_right_and_left() {
         let mut game = Game::new();
 
-        game.move_right();
-        game.move_left();
+        game.move_right().unwrap();
+        game.move_left().unwrap();
 
         assert!(count_filled_fields(game.get_field()) > 1);
     }
     
+    #[cfg(test)]
+    mod with_seed {
+        use crate::game::tests::*;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        fn play(game: &mut Game<impl rand::Rng + Clone>) {
+            for _ in 0..20 {
+                game.move_down().ok();
+                game.move_left().ok();
+                game.move_up().ok();
+                game.move_right().ok();
+            }
+        }
+
+        #[test]
+        fn it_should_initialize_the_same_field_for_the_same_seed() {
+            assert_eq!(
+                Game::with_seed(7).get_field(),
+                Game::with_seed(7).get_field()
+            );
+        }
+
+        #[test]
+        fn it_should_reproduce_fields_and_score_for_the_same_seed_and_moves() {
+            let mut first = Game::with_seed(2048);
+            let mut second = Game::with_seed(2048);
+            play(&mut first);
+            play(&mut second);
+
+            assert_eq!(first.get_field().values, second.get_field().values);
+            assert_eq!(first.get_score(), second.get_score());
+        }
+
+        #[test]
+        fn it_should_use_the_given_rng() {
+            let mut first = Game::with_rng(StdRng::seed_from_u64(1));
+            let mut second = Game::with_rng(StdRng::seed_from_u64(1));
+            play(&mut first);
+            play(&mut second);
+
+            assert_eq!(first.get_field().values, second.get_field().values);
+        }
+    }
+
     #[cfg(test)]
     mod from_field {
+        use crate::board::Board;
         use crate::field::Field;
         use crate::game::tests::*;
 
@@ -160,11 +518,199 @@ mod tests {
             let game = Game::from_field(NON_EMPTY_FIELD);
 
             assert_eq!(
-                game.get_field(),
+                game.get_field().values,
                 [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
             );
             assert_eq!(game.get_score(), 0);
         }
+
+        #[test]
+        fn it_should_initialize_game_with_given_board() {
+            let board =
+                Board::from_values([[2, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 4]]);
+            let game = Game::from_field(board.unwrap());
+
+            assert_eq!(
+                game.get_field().values,
+                [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4]
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod move_outcome {
+        use crate::game::tests::*;
+
+        #[test]
+        fn it_should_return_the_outcome_of_the_move() {
+            let mut game =
+                Game::from_field([[X, X, X, X], [X, X, X, X], [TWO, TWO, X, X], [X, X, X, X]]);
+
+            let outcome = game.move_left().unwrap();
+
+            assert!(outcome.moved);
+            assert_eq!(outcome.points, 4);
+            assert_eq!(outcome.merges.len(), 1);
+            assert_eq!(outcome.spawned.len(), 1);
+            assert_eq!(game.get_score(), 4);
+        }
+
+        #[test]
+        fn it_should_return_an_unmoved_outcome_if_nothing_changed() {
+            let mut game =
+                Game::from_field([[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]]);
+
+            let outcome = game.move_left().unwrap();
+
+            assert!(!outcome.moved);
+            assert!(outcome.spawned.is_empty());
+        }
+    }
+
+    #[cfg(test)]
+    mod make_move {
+        use crate::direction::Direction;
+        use crate::game::tests::*;
+
+        #[test]
+        fn it_should_move_in_every_direction() {
+            for direction in Direction::ALL {
+                let mut game =
+                    Game::from_field([[X, X, X, X], [X, TWO, X, X], [X, X, X, X], [X, X, X, X]]);
+
+                let outcome = game.make_move(direction).unwrap();
+
+                assert!(outcome.moved, "{} did not move", direction);
+            }
+        }
+
+        #[test]
+        fn it_should_behave_like_the_single_direction_methods() {
+            let mut first = Game::with_seed(3);
+            let mut second = Game::with_seed(3);
+            for _ in 0..10 {
+                assert_eq!(first.make_move(Direction::Up), second.move_up());
+                assert_eq!(first.make_move(Direction::Left), second.move_left());
+            }
+            assert_eq!(first.get_field().values, second.get_field().values);
+        }
+    }
+
+    #[cfg(test)]
+    mod apply_move {
+        use crate::board::Board;
+        use crate::direction::Direction;
+        use crate::game::apply_move;
+        use crate::game::tests::*;
+
+        #[test]
+        fn it_should_move_and_merge_without_spawning() {
+            let board = Board::from([[X, X, X, X], [X, X, X, X], [X, X, X, X], [TWO, X, X, TWO]]);
+
+            let (moved, outcome) = apply_move(board, Direction::Right);
+
+            assert_eq!(moved.get((3, 3)), Some(4));
+            assert_eq!(outcome.points, 4);
+            assert_eq!(outcome.merges.len(), 1);
+            assert!(outcome.spawned.is_empty());
+            assert_eq!(moved.empty_cells().len(), 15);
+            assert_eq!(apply_move(board, Direction::Right), (moved, outcome));
+        }
+
+        #[test]
+        fn it_should_merge_every_tile_once() {
+            let board = Board::from([
+                [X, X, X, X],
+                [X, X, X, X],
+                [X, X, X, X],
+                [TWO, TWO, FOUR, X],
+            ]);
+
+            let (moved, outcome) = apply_move(board, Direction::Left);
+
+            assert_eq!(moved.get((3, 0)), Some(4));
+            assert_eq!(moved.get((3, 1)), Some(4));
+            assert_eq!(outcome.points, 4);
+        }
+
+        #[test]
+        fn it_should_report_if_nothing_moved() {
+            let board = Board::from([[X, X, X, X], [X, X, X, X], [X, X, X, X], [TWO, X, X, X]]);
+
+            let (moved, outcome) = apply_move(board, Direction::Left);
+
+            assert_eq!(moved, board);
+            assert!(!outcome.moved);
+            assert!(!outcome.lost);
+        }
+    }
+
+    #[cfg(test)]
+    mod with_tile_tracking {
+        use crate::game::tests::*;
+        use crate::tile_tracking::TileEvent;
+
+        #[test]
+        fn it_should_not_report_events_without_tracking() {
+            let mut game =
+                Game::from_field([[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]]);
+
+            let outcome = game.move_left().unwrap();
+
+            assert!(outcome.events.is_empty());
+            assert_eq!(game.get_tile_ids(), None);
+        }
+
+        #[test]
+        fn it_should_report_the_events_of_a_move() {
+            let mut game =
+                Game::from_field([[X, X, X, X], [X, X, X, X], [X, X, X, TWO], [X, X, X, TWO]])
+                    .with_tile_tracking();
+
+            let outcome = game.move_down().unwrap();
+            let spawned = outcome.spawned[0];
+
+            assert_eq!(
+                outcome.events,
+                vec![
+                    TileEvent::TilesMerged {
+                        ids: [1, 0],
+                        into: (3, 3),
+                        value: 4
+                    },
+                    TileEvent::TileSpawned {
+                        id: 2,
+                        at: spawned.cell,
+                        value: spawned.value
+                    }
+                ]
+            );
+            let ids = game.get_tile_ids().unwrap();
+            assert_eq!(ids[3][3], Some(1));
+            assert_eq!(ids[spawned.cell.0][spawned.cell.1], Some(2));
+        }
+    }
+
+    #[cfg(test)]
+    mod with_spawner {
+        use crate::game::tests::*;
+        use crate::tile_spawner::ScriptedSpawner;
+
+        #[test]
+        fn it_should_use_the_given_spawner_after_a_move() {
+            let spawner = ScriptedSpawner::new([((0, 0), 4)]).unwrap();
+            let mut game =
+                Game::from_field([[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]])
+                    .with_spawner(spawner);
+
+            game.move_down().unwrap();
+            game.move_left().unwrap();
+
+            assert_eq!(
+                game.get_field().values,
+                [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]
+            );
+        }
     }
 
     #[cfg(test)]
@@ -181,7 +727,7 @@ mod tests {
                 [TWO, FOUR, TWO, FOUR],
             ];
             let game = Game::from_field(FULL_FIELD);
-            assert_eq!(true, game.is_game_over());
+            assert!(game.is_game_over());
         }
 
         #[test]
@@ -193,7 +739,360 @@ mod tests {
                 [X, TWO, X, X],
             ];
             let game = Game::from_field(NEARL_EMPTY_FIELD);
-            assert_eq!(false, game.is_game_over());
+            assert!(!game.is_game_over());
+        }
+    }
+
+    #[cfg(test)]
+    mod available_moves {
+        use crate::direction::Direction;
+        use crate::field::Field;
+        use crate::game::tests::*;
+
+        #[test]
+        fn it_should_return_the_moves_which_change_the_field() {
+            let game = Game::from_field([[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]]);
+            assert_eq!(
+                game.available_moves().collect::<Vec<Direction>>(),
+                vec![Direction::Up, Direction::Left]
+            );
+            assert!(game.can_move(Direction::Up));
+            assert!(!game.can_move(Direction::Down));
+        }
+
+        #[test]
+        fn it_should_return_no_moves_if_game_is_over() {
+            const FULL_FIELD: Field = [
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+            ];
+            let game = Game::from_field(FULL_FIELD);
+            assert!(game.is_game_over());
+            assert_eq!(game.available_moves().count(), 0);
+        }
+    }
+
+    #[cfg(test)]
+    mod other_sizes {
+        use crate::board::Board;
+        use crate::direction::Direction;
+        use crate::game::tests::*;
+        use crate::game::GameRng;
+        use crate::game_status::GameStatus;
+        use rand::SeedableRng;
+
+        #[test]
+        fn it_should_start_a_3x3_game_with_two_tiles() {
+            let game = Game::<GameRng, 3>::with_rng(GameRng::seed_from_u64(7));
+            assert_eq!(game.get_field().values.len(), 9);
+            assert_eq!(game.get_board().empty_cells().len(), 7);
+        }
+
+        #[test]
+        fn it_should_move_merge_and_score_on_a_6x6_board() {
+            let mut values = [[0; 6]; 6];
+            values[5][0] = 2;
+            values[5][5] = 2;
+            let board = Board::from_values(values).unwrap();
+            let mut game = Game::from_field_with_rng(board, GameRng::seed_from_u64(7));
+
+            let outcome = game.make_move(Direction::Left).unwrap();
+
+            assert_eq!(game.get_board().get((5, 0)), Some(4));
+            assert_eq!(outcome.points, 4);
+            assert_eq!(game.get_board().empty_cells().len(), 34);
+        }
+
+        #[test]
+        fn it_should_play_on_a_tall_board() {
+            let mut game = Game::<GameRng, 4, 6>::with_rng(GameRng::seed_from_u64(7));
+            while let Some(direction) = game.available_moves().next() {
+                game.make_move(direction).unwrap();
+            }
+
+            let field = game.get_field();
+            assert_eq!(field.width, 4);
+            assert_eq!(field.height(), 6);
+            assert_eq!(game.get_board().empty_cells().len(), 0);
+        }
+
+        #[test]
+        fn it_should_play_an_8x8_game_until_it_is_over() {
+            let mut game = Game::<GameRng, 8>::with_rng(GameRng::seed_from_u64(7));
+            while let Some(direction) = game.available_moves().next() {
+                game.make_move(direction).unwrap();
+                if game.status() == GameStatus::Won {
+                    game.continue_after_win().unwrap();
+                }
+            }
+            assert!(game.is_game_over());
+        }
+    }
+
+    #[cfg(test)]
+    mod undo {
+        use crate::direction::Direction;
+        use crate::game::tests::*;
+        use crate::game_status::GameError;
+        use crate::history::HistoryDepth;
+
+        #[test]
+        fn it_should_restore_the_board_and_score() {
+            let mut game =
+                Game::from_field([[X, X, X, X], [X, X, X, X], [X, X, X, X], [TWO, TWO, X, X]])
+                    .with_history(HistoryDepth::Unlimited);
+            let before = game.get_field().values;
+
+            game.move_left().unwrap();
+            game.undo().unwrap();
+
+            assert_eq!(game.get_field().values, before);
+            assert_eq!(game.get_score(), 0);
+            assert_eq!(game.get_move_count(), 0);
+        }
+
+        #[test]
+        fn it_should_spawn_the_same_tile_when_repeating_an_undone_move() {
+            let mut game = Game::with_seed(7).with_history(HistoryDepth::Unlimited);
+
+            let first = game.make_move(Direction::Down).unwrap();
+            game.undo().unwrap();
+            let second = game.make_move(Direction::Down).unwrap();
+
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn it_should_redo_an_undone_move() {
+            let mut game = Game::with_seed(7).with_history(HistoryDepth::Unlimited);
+            game.move_down().unwrap();
+            game.move_left().unwrap();
+            let field = game.get_field().values;
+            let score = game.get_score();
+
+            game.undo().unwrap();
+            game.undo().unwrap();
+            game.redo().unwrap();
+            game.redo().unwrap();
+
+            assert_eq!(game.get_field().values, field);
+            assert_eq!(game.get_score(), score);
+            assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+        }
+
+        #[test]
+        fn it_should_keep_only_the_configured_number_of_moves() {
+            let mut game = Game::with_seed(7).with_history(HistoryDepth::Limited(1));
+            game.move_down().unwrap();
+            game.move_left().unwrap();
+
+            game.undo().unwrap();
+
+            assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+        }
+
+        #[test]
+        fn it_should_not_undo_without_history() {
+            let mut game = Game::with_seed(7);
+            game.move_down().unwrap();
+            assert!(!game.can_undo());
+            assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+        }
+
+        #[test]
+        fn it_should_use_up_the_undo_tokens() {
+            let mut game = Game::with_seed(7)
+                .with_history(HistoryDepth::Unlimited)
+                .with_undo_tokens(1);
+            game.move_down().unwrap();
+            game.move_left().unwrap();
+
+            game.undo().unwrap();
+
+            assert_eq!(game.get_undo_tokens(), Some(0));
+            assert!(!game.can_undo());
+            assert_eq!(game.undo(), Err(GameError::NoUndoTokensLeft));
+        }
+    }
+
+    #[cfg(test)]
+    mod status {
+        use crate::direction::Direction;
+        use crate::game::tests::*;
+        use crate::game_status::{GameError, GameStatus};
+        use crate::tile_spawner::ScriptedSpawner;
+
+        #[test]
+        fn it_should_start_playing() {
+            assert_eq!(Game::new().status(), GameStatus::Playing);
+        }
+
+        #[test]
+        fn it_should_be_won_once_the_target_is_reached() {
+            let mut game = Game::from_field([
+                [X, X, X, X],
+                [X, X, X, X],
+                [X, X, X, X],
+                [EIGHT, EIGHT, X, X],
+            ])
+            .with_target(16);
+
+            let outcome = game.move_left().unwrap();
+
+            assert!(outcome.won);
+            assert_eq!(game.status(), GameStatus::Won);
+        }
+
+        #[test]
+        fn it_should_not_be_won_by_a_target_already_on_the_board() {
+            let mut game = Game::from_field([
+                [SIXTEEN, X, X, X],
+                [X, X, X, X],
+                [X, X, X, X],
+                [EIGHT, EIGHT, X, X],
+            ])
+            .with_target(16);
+
+            let outcome = game.move_down().unwrap();
+
+            assert!(!outcome.won);
+            assert_eq!(game.status(), GameStatus::Playing);
+
+            game.move_left().unwrap();
+
+            assert_eq!(game.status(), GameStatus::Won);
+        }
+
+        #[test]
+        fn it_should_reject_moves_until_continued_after_win() {
+            let mut game = Game::from_field([
+                [X, X, X, X],
+                [X, X, X, X],
+                [X, X, X, X],
+                [EIGHT, EIGHT, X, X],
+            ])
+            .with_target(16);
+            game.move_left().unwrap();
+
+            assert_eq!(game.move_right(), Err(GameError::GameWon));
+            game.continue_after_win().unwrap();
+
+            assert_eq!(game.status(), GameStatus::ContinuePlaying);
+            assert!(game.move_right().is_ok());
+            assert_eq!(game.status(), GameStatus::ContinuePlaying);
+        }
+
+        #[test]
+        fn it_should_only_continue_a_won_game() {
+            let mut game = Game::new();
+            assert_eq!(game.continue_after_win(), Err(GameError::NotWon));
+        }
+
+        #[test]
+        fn it_should_be_lost_if_no_move_is_possible() {
+            let mut game = Game::from_field([
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+                [X, SIXTEEN, EIGHT, SIXTEEN],
+            ])
+            .with_spawner(ScriptedSpawner::new([((3, 3), 2)]).unwrap());
+
+            game.move_left().unwrap();
+
+            assert_eq!(game.status(), GameStatus::Lost);
+            assert_eq!(game.make_move(Direction::Up), Err(GameError::GameOver));
+        }
+
+        #[test]
+        fn it_should_start_lost_for_a_field_without_moves() {
+            let game = Game::from_field([
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+            ]);
+            assert_eq!(game.status(), GameStatus::Lost);
+        }
+    }
+
+    #[cfg(test)]
+    mod rules {
+        use crate::game::tests::*;
+        use crate::game::GameRng;
+        use crate::history::HistoryDepth;
+        use crate::tile_spawner::ScriptedSpawner;
+        use crate::tile_tracking::TileEvent;
+        use rand::SeedableRng;
+
+        #[test]
+        fn it_should_keep_the_rules() {
+            let rules = Rules::classic().with_spawns_per_move(2);
+            let game: Game = Game::with_rules_and_rng(rules.clone(), GameRng::seed_from_u64(1));
+            assert_eq!(game.rules(), &rules);
+        }
+
+        #[test]
+        fn it_should_update_the_target_of_the_rules() {
+            let game = Game::new().with_target(512);
+            assert_eq!(game.rules().target(), 512);
+        }
+
+        #[test]
+        fn it_should_track_chained_merges() {
+            let rules = Rules::classic().with_chain_merges(true);
+            let mut game = Game::from_field_with_rules_and_rng(
+                [[TWO, TWO, FOUR, X], [X; 4], [X; 4], [X; 4]],
+                rules,
+                GameRng::seed_from_u64(1),
+            )
+            .with_spawner(ScriptedSpawner::new([]).unwrap())
+            .with_tile_tracking();
+
+            let outcome = game.move_left().unwrap();
+
+            assert_eq!(game.get_board().get((0, 0)), Some(8));
+            assert_eq!(
+                outcome.events,
+                vec![
+                    TileEvent::TilesMerged {
+                        ids: [0, 1],
+                        into: (0, 0),
+                        value: 4
+                    },
+                    TileEvent::TileMoved {
+                        id: 2,
+                        from: (0, 2),
+                        to: (0, 1)
+                    },
+                    TileEvent::TilesMerged {
+                        ids: [0, 2],
+                        into: (0, 0),
+                        value: 8
+                    },
+                ]
+            );
+            assert_eq!(game.get_tile_ids().unwrap()[0][0], Some(0));
+        }
+
+        #[test]
+        fn it_should_undo_a_spawn_after_a_move_which_changed_nothing() {
+            let rules = Rules::classic().with_spawn_on_unchanged_move(true);
+            let mut game = Game::from_field_with_rules_and_rng(
+                [[TWO, X, X, X], [X; 4], [X; 4], [X; 4]],
+                rules,
+                GameRng::seed_from_u64(1),
+            )
+            .with_history(HistoryDepth::Unlimited);
+
+            let outcome = game.move_up().unwrap();
+            assert!(!outcome.moved);
+            assert_eq!(outcome.spawned.len(), 1);
+
+            game.undo().unwrap();
+            assert_eq!(game.get_board().empty_cells().len(), 15);
         }
     }
 
@@ -208,11 +1107,11 @@ mod tests {
         use crate::game::tests::*;
         #[test]
         fn it_moves_initial_value_down() {
-            let mut game = Game::new();
+            let mut game = new_game_with_one_tile();
 
-            game.move_down();
+            game.move_down().unwrap();
 
-            let field = game.get_field();
+            let field = game.get_field().values;
             let sum_of_values_in_last_row = field[12] + field[13] + field[14] + field[15];
             assert_ne!(sum_of_values_in_last_row, 0);
             assert_eq!(game.get_score(), 0);
@@ -222,7 +1121,7 @@ mod tests {
         fn it_should_add_4_to_highscore_after_merge() {
             let mut game =
                 Game::from_field([[X, X, X, X], [X, X, X, X], [TWO, X, X, X], [TWO, X, X, X]]);
-            game.move_down();
+            game.move_down().unwrap();
             assert_eq!(game.get_score(), 4);
         }
     }
@@ -232,11 +1131,11 @@ mod tests {
         use crate::game::tests::*;
         #[test]
         fn it_moves_initial_value_right() {
-            let mut game = Game::new();
+            let mut game = new_game_with_one_tile();
 
-            game.move_right();
+            game.move_right().unwrap();
 
-            let field = game.get_field();
+            let field = game.get_field().values;
             let sum_of_values_in_last_row = field[3] + field[7] + field[11] + field[15];
             assert_ne!(sum_of_values_in_last_row, 0);
             assert_eq!(game.get_score(), 0);
@@ -246,7 +1145,7 @@ mod tests {
         fn it_should_add_4_to_highscore_after_merge() {
             let mut game =
                 Game::from_field([[X, X, X, X], [X, X, X, X], [TWO, TWO, X, X], [X, X, X, X]]);
-            game.move_right();
+            game.move_right().unwrap();
             assert_eq!(game.get_score(), 4);
         }
     }
@@ -255,11 +1154,11 @@ mod tests {
         use crate::game::tests::*;
         #[test]
         fn it_moves_initial_value_up() {
-            let mut game = Game::new();
+            let mut game = new_game_with_one_tile();
 
-            game.move_up();
+            game.move_up().unwrap();
 
-            let field = game.get_field();
+            let field = game.get_field().values;
             let sum_of_values_in_last_row = field[0] + field[1] + field[2] + field[3];
             assert_ne!(sum_of_values_in_last_row, 0);
             assert_eq!(game.get_score(), 0);
@@ -269,7 +1168,7 @@ mod tests {
         fn it_should_add_4_to_highscore_after_merge() {
             let mut game =
                 Game::from_field([[X, X, X, X], [X, X, X, X], [TWO, X, X, X], [TWO, X, X, X]]);
-            game.move_up();
+            game.move_up().unwrap();
             assert_eq!(game.get_score(), 4);
         }
     }
@@ -279,11 +1178,11 @@ mod tests {
         use crate::game::tests::*;
         #[test]
         fn it_moves_initial_value_left() {
-            let mut game = Game::new();
+            let mut game = new_game_with_one_tile();
 
-            game.move_left();
+            game.move_left().unwrap();
 
-            let field = game.get_field();
+            let field = game.get_field().values;
             let sum_of_values_in_last_row = field[0] + field[4] + field[8] + field[12];
             assert_ne!(sum_of_values_in_last_row, 0);
             assert_eq!(game.get_score(), 0);
@@ -293,7 +1192,7 @@ mod tests {
         fn it_should_add_4_to_highscore_after_merge() {
             let mut game =
                 Game::from_field([[X, X, X, X], [X, X, X, X], [TWO, TWO, X, X], [X, X, X, X]]);
-            game.move_left();
+            game.move_left().unwrap();
             assert_eq!(game.get_score(), 4);
         }
     }
@@ -1,146 +1,142 @@
+use crate::board::Board;
+use crate::direction::Direction;
 use crate::field::Field;
-use crate::field_add_random_value::add_value;
+use crate::field_gameover::is_game_over;
 use crate::field_move_and_merge::*;
+use crate::move_outcome::{Merge, MoveOutcome, SpawnedTile};
+use crate::random::random;
+use crate::rules::Rules;
 use crate::score_calculator::*;
-use crate::random::RandomNumber;
-
-pub fn move_field_down(
-    field: Field,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> (Field, Score) {
-    move_field(
-        field,
-        move_and_merge_down,
-        calculate_added_points_down,
-        random_number_value,
-        random_number_position,
-    )
-}
-
-pub fn move_field_right(
-    field: Field,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> (Field, Score) {
-    move_field(
-        field,
-        move_and_merge_right,
-        calculate_added_points_right,
-        random_number_value,
-        random_number_position,
-    )
+use crate::tile_spawner::TileSpawner;
+use rand::Rng;
+
+/// Moves, merges and spawns with the rules. Also returns the steps of the
+/// move, which a tile tracker turns into tile events.
+pub fn move_field<R: Rng + ?Sized, const W: usize, const H: usize>(
+    field: Field<W, H>,
+    direction: Direction,
+    rules: &Rules,
+    spawner: &mut dyn TileSpawner<W, H>,
+    rng: &mut R,
+) -> (Field<W, H>, MoveOutcome, Vec<CellEvent>) {
+    let (moved, merges, events) = move_and_merge_traced(field, direction, rules.chain_merges());
+    let mut outcome = MoveOutcome {
+        moved: field != moved,
+        points: calculate_points(&merges),
+        merges,
+        ..MoveOutcome::default()
+    };
+    let mut result = moved;
+    if outcome.moved || rules.spawns_on_unchanged_move() {
+        result = spawn_tiles(moved, rules.spawns_per_move(), spawner, rng);
+        outcome.spawned = find_spawned_tiles(moved, result);
+    }
+    outcome.won = creates_target(&outcome.merges, rules.target());
+    outcome.lost = is_game_over(result);
+    (result, outcome, events)
 }
 
-pub fn move_field_up(
-    field: Field,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> (Field, Score) {
-    move_field(
-        field,
-        move_and_merge_up,
-        calculate_added_points_up,
-        random_number_value,
-        random_number_position,
-    )
+/// Lets the spawner place `count` tiles one after another, as long as there
+/// is room.
+fn spawn_tiles<R: Rng + ?Sized, const W: usize, const H: usize>(
+    field: Field<W, H>,
+    count: usize,
+    spawner: &mut dyn TileSpawner<W, H>,
+    rng: &mut R,
+) -> Field<W, H> {
+    (0..count).fold(field, |field, _| {
+        let random_number_value = random(rng);
+        let random_number_position = random(rng);
+        spawner
+            .spawn(
+                Board::from(field),
+                random_number_value,
+                random_number_position,
+            )
+            .field()
+    })
 }
 
-pub fn move_field_left(
-    field: Field,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> (Field, Score) {
-    move_field(
-        field,
-        move_and_merge_left,
-        calculate_added_points_left,
-        random_number_value,
-        random_number_position,
-    )
+fn find_spawned_tiles<const W: usize, const H: usize>(
+    before: Field<W, H>,
+    after: Field<W, H>,
+) -> Vec<SpawnedTile> {
+    let after = Board::from(after);
+    Board::from(before)
+        .empty_cells()
+        .into_iter()
+        .filter_map(|cell| match after.get(cell) {
+            Some(0) | None => None,
+            Some(value) => Some(SpawnedTile { cell, value }),
+        })
+        .collect()
 }
 
-fn move_field(
-    field: Field,
-    move_and_merge_operation: fn(Field) -> Field,
-    highscore_calculate_function: fn(Field, Field) -> Score,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> (Field, Score) {
-    let mut moved = move_and_merge_operation(field);
-    let added_points = highscore_calculate_function(field, moved);
-    if field != moved {
-        moved = add_value(moved, random_number_value, random_number_position);
-    }
-    (moved, added_points)
+/// Whether one of the merges created a tile of at least the target value.
+/// Tiles which were on the board before the move do not count.
+pub fn creates_target(merges: &[Merge], target: u64) -> bool {
+    merges.iter().any(|merge| merge.value >= target)
 }
 
-pub fn initialize_field(
-    field: Field,
-    random_number_value: RandomNumber,
-    random_number_position: RandomNumber,
-) -> Field {
-    add_value(field, random_number_value, random_number_position)
+/// Places the starting tiles of the rules on the field.
+pub fn initialize_field<R: Rng + ?Sized, const W: usize, const H: usize>(
+    field: Field<W, H>,
+    rules: &Rules,
+    rng: &mut R,
+) -> Field<W, H> {
+    spawn_tiles(field, rules.starting_tiles(), &mut rules.spawner(), rng)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::field::tests::*;
     use crate::game_actions::*;
+    use crate::move_outcome::Merge;
+    use crate::tile_spawner::{ScriptedSpawner, UniformSpawner};
+    use rand::rngs::mock::StepRng;
+
+    /// Moves with the classic rules, every random number is `0`.
+    fn move_field_classic(
+        field: Field,
+        direction: Direction,
+        spawner: &mut dyn TileSpawner,
+    ) -> (Field, MoveOutcome) {
+        let (field, outcome, _) = move_field(
+            field,
+            direction,
+            &Rules::classic(),
+            spawner,
+            &mut StepRng::new(0, 0),
+        );
+        (field, outcome)
+    }
 
     #[cfg(test)]
     mod move_field_down {
         use crate::game_actions::tests::*;
         #[test]
         fn it_should_perform_the_action() {
-            #[rustfmt::skip]
-            let source_field = [
-                [TWO, TWO, X, TWO],
-                [TWO, X, X, TWO],
-                [X, X, X, TWO],
-                [X, X, TWO, TWO]
-            ];
-            let (new_field, _) = move_field_down(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [TWO, X, X, X],
-                [X, X, X, X],
-                [X, X, X, FOUR],
-                [FOUR, TWO, TWO, FOUR]
-            ]);
+            let source_field = field("2,2.2/2..2/...2/..2,2");
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Down, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("2.../..../...4/4,2,2,4"));
         }
 
         #[test]
         fn it_should_not_add_value_if_nothing_changed() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, X, X, X]
-            ];
-            let (new_field, _) = move_field_down(source_field, 0.0, 0.0);
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, X, X, X]
-            ]);
+            let source_field = field("..../..../..../2...");
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Down, &mut UniformSpawner);
+            assert_eq!(new_field, field("..../..../..../2..."));
         }
 
         #[test]
         fn it_should_return_added_points() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, X, X, X],
-                [TWO, X, X, X]
-            ];
-            let (_, actual_added_points) = move_field_down(source_field, 0.0, 0.0);
-            assert_eq!(actual_added_points, 4);
+            let source_field = field("..../..../2.../2...");
+            let (_, outcome) =
+                move_field_classic(source_field, Direction::Down, &mut UniformSpawner);
+            assert_eq!(outcome.points, 4);
         }
     }
 
@@ -149,58 +145,31 @@ mod tests {
         use crate::game_actions::tests::*;
         #[test]
         fn it_should_perform_the_action() {
-            #[rustfmt::skip]
-            let source_field = [
-                [TWO, TWO, X, TWO],
-                [TWO, X, X, TWO],
-                [X, X, X, TWO],
-                [X, X, TWO, TWO]
-            ];
-
-            let (new_field, _) = move_field_right(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [TWO, X, TWO, FOUR],
-                [X, X, X, FOUR],
-                [X, X, X, TWO],
-                [X, X, X, FOUR]
-            ]);
+            let source_field = field("2,2.2/2..2/...2/..2,2");
+
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Right, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("2.2,4/...4/...2/...4"));
         }
 
         #[test]
         fn it_should_not_add_value_if_nothing_changed() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, X, X, TWO],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ];
-
-            let (new_field, _) = move_field_right(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [X, X, X, TWO],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ]);
+            let source_field = field("...2/..../..../....");
+
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Right, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("...2/..../..../...."));
         }
 
         #[test]
         fn it_should_return_added_points() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, X, TWO, TWO],
-                [X, X, X, X],
-                [X, TWO, TWO, X],
-                [X, X, X, X]
-            ];
-
-            let (_, actual_added_points) = move_field_right(source_field, 0.0, 0.0);
-            assert_eq!(actual_added_points, 8);
+            let source_field = field("..2,2/..../.2,2./....");
+
+            let (_, outcome) =
+                move_field_classic(source_field, Direction::Right, &mut UniformSpawner);
+            assert_eq!(outcome.points, 8);
         }
     }
 
@@ -210,59 +179,31 @@ mod tests {
 
         #[test]
         fn it_should_perform_the_action() {
-            #[rustfmt::skip]
-            let source_field = [
-                [TWO, TWO, X, TWO],
-                [TWO, X, X, TWO],
-                [X, X, X, TWO],
-                [X, X, TWO, TWO]
-            ];
-
-            let (new_field, _) = move_field_up(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [FOUR, TWO , TWO, FOUR],
-                [TWO, X, X, FOUR],
-                [X, X, X, X],
-                [X, X, X, X]
-            ]);
+            let source_field = field("2,2.2/2..2/...2/..2,2");
+
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Up, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("4,2,2,4/2..4/..../...."));
         }
 
         #[test]
         fn it_should_not_add_value_if_nothing_changed() {
-            #[rustfmt::skip]
-            let source_field= [
-                [TWO, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ];
-
-            let (new_field, _) = move_field_up(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [TWO, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ]);
+            let source_field = field("2.../..../..../....");
+
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Up, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("2.../..../..../...."));
         }
 
         #[test]
         fn it_should_return_added_points() {
-            #[rustfmt::skip]
-            let source_field = [
-                [TWO, X, X, FOUR],
-                [TWO, X, X, FOUR],
-                [X, X, X, X],
-                [X, X, X, X]
-            ];
+            let source_field = field("2..4/2..4/..../....");
 
-            let (_, actual_added_points) = move_field_up(source_field, 0.0, 0.0);
+            let (_, outcome) = move_field_classic(source_field, Direction::Up, &mut UniformSpawner);
 
-            assert_eq!(actual_added_points, 12);
+            assert_eq!(outcome.points, 12);
         }
     }
 
@@ -271,59 +212,215 @@ mod tests {
         use crate::game_actions::tests::*;
         #[test]
         fn it_should_perform_the_action() {
-            #[rustfmt::skip]
-            let source_field = [
-                [TWO, TWO, X, TWO],
-                [TWO, X, X, TWO],
-                [X, X, X, TWO],
-                [X, X, TWO, TWO]
-            ];
-
-            let (new_field, _) = move_field_left(source_field, 0.99, 0.99);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [FOUR, TWO, X, X],
-                [FOUR, X, X, X],
-                [TWO, X, X, X],
-                [FOUR, X, X, FOUR]
-            ]);
+            let source_field = field("2,2.2/2..2/...2/..2,2");
+
+            // Every random number is just below 1
+            let (new_field, _, _) = move_field(
+                source_field,
+                Direction::Left,
+                &Rules::classic(),
+                &mut UniformSpawner,
+                &mut StepRng::new(u64::MAX, 0),
+            );
+
+            assert_eq!(new_field, field("4,2../4.../2.../4..4"));
         }
 
         #[test]
         fn it_should_not_add_value_if_nothing_changed() {
-            #[rustfmt::skip]
-            let source_field= [
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, X, X, X]
-            ];
-
-            let (new_field, _) = move_field_left(source_field, 0.0, 0.0);
-
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, X, X, X]
-            ]);
+            let source_field = field("..../..../..../2...");
+
+            let (new_field, _) =
+                move_field_classic(source_field, Direction::Left, &mut UniformSpawner);
+
+            assert_eq!(new_field, field("..../..../..../2..."));
         }
 
         #[test]
         fn it_should_return_added_points() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, FOUR, FOUR, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [TWO, TWO, TWO, TWO]
-            ];
+            let source_field = field(".4,4./..../..../2,2,2,2");
 
-            let (_, actual_added_points) = move_field_left(source_field, 0.0, 0.0);
+            let (_, outcome) =
+                move_field_classic(source_field, Direction::Left, &mut UniformSpawner);
 
-            assert_eq!(actual_added_points, 16);
+            assert_eq!(outcome.points, 16);
+        }
+    }
+
+    #[cfg(test)]
+    mod move_outcome {
+        use crate::game_actions::tests::*;
+
+        #[test]
+        fn it_should_report_merges_with_their_cells() {
+            let source_field = field("..../2.../.4../2,4..");
+
+            let (_, outcome) =
+                move_field_classic(source_field, Direction::Down, &mut UniformSpawner);
+
+            assert_eq!(
+                outcome.merges,
+                vec![
+                    Merge {
+                        sources: [(3, 0), (1, 0)],
+                        target: (3, 0),
+                        value: 4
+                    },
+                    Merge {
+                        sources: [(3, 1), (2, 1)],
+                        target: (3, 1),
+                        value: 8
+                    }
+                ]
+            );
+            assert_eq!(outcome.points, 12);
+        }
+
+        #[test]
+        fn it_should_report_the_spawned_tile() {
+            let source_field = field("..../..../..../...2");
+
+            let mut spawner = ScriptedSpawner::new([((0, 0), 4)]).unwrap();
+
+            let (_, outcome) = move_field_classic(source_field, Direction::Left, &mut spawner);
+
+            assert!(outcome.moved);
+            assert_eq!(
+                outcome.spawned,
+                vec![SpawnedTile {
+                    cell: (0, 0),
+                    value: 4
+                }]
+            );
+        }
+
+        #[test]
+        fn it_should_report_a_move_which_changed_nothing() {
+            let source_field = field("2.../..../..../....");
+
+            let (_, outcome) = move_field_classic(source_field, Direction::Up, &mut UniformSpawner);
+
+            assert_eq!(outcome, MoveOutcome::default());
+        }
+
+        #[test]
+        fn it_should_report_a_lost_game() {
+            let source_field = field("2,4,2,4/4,2,4,2/2,4,2,4/.16,8,16");
+
+            let (_, outcome) =
+                move_field_classic(source_field, Direction::Left, &mut UniformSpawner);
+
+            assert!(outcome.lost);
+        }
+    }
+
+    #[cfg(test)]
+    mod move_field_with_spawner {
+        use crate::game_actions::tests::*;
+
+        #[test]
+        fn it_should_let_the_spawner_place_the_new_tile() {
+            let mut spawner = ScriptedSpawner::new([((0, 3), 4)]).unwrap();
+            let source_field = field("..../..../..../...2");
+
+            let (new_field, _) = move_field_classic(source_field, Direction::Left, &mut spawner);
+
+            assert_eq!(new_field, field("...4/..../..../2..."));
+        }
+    }
+
+    #[cfg(test)]
+    mod move_field_with_rules {
+        use crate::game_actions::tests::*;
+
+        fn move_field_with(
+            field: Field,
+            direction: Direction,
+            rules: &Rules,
+        ) -> (Field, MoveOutcome) {
+            let mut spawner = rules.spawner();
+            let (field, outcome, _) = move_field(
+                field,
+                direction,
+                rules,
+                &mut spawner,
+                &mut StepRng::new(0, 0),
+            );
+            (field, outcome)
+        }
+
+        #[test]
+        fn it_should_spawn_the_configured_number_of_tiles() {
+            let rules = Rules::classic().with_spawns_per_move(3);
+            let source_field = field("...2/..../..../....");
+
+            let (new_field, outcome) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert_eq!(outcome.spawned.len(), 3);
+            assert_eq!(new_field[0], [TWO, TWO, TWO, TWO]);
+        }
+
+        #[test]
+        fn it_should_stop_spawning_once_the_field_is_full() {
+            let rules = Rules::classic().with_spawns_per_move(3);
+            let source_field = field("2,4,2,4/4,2,4,2/2,4,2,4/..4,2");
+
+            let (_, outcome) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert_eq!(outcome.spawned.len(), 2);
+        }
+
+        #[test]
+        fn it_should_spawn_after_a_move_which_changed_nothing_if_configured() {
+            let rules = Rules::classic().with_spawn_on_unchanged_move(true);
+            let source_field = field("2.../..../..../....");
+
+            let (new_field, outcome) = move_field_with(source_field, Direction::Up, &rules);
+
+            assert!(!outcome.moved);
+            assert_eq!(new_field[0], [TWO, TWO, X, X]);
+        }
+
+        #[test]
+        fn it_should_spawn_the_configured_values() {
+            let rules = Rules::classic().with_spawn_weights([(8, 1.0)]).unwrap();
+            let source_field = field(".2../..../..../....");
+
+            let (new_field, _) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert_eq!(new_field[0], [TWO, EIGHT, X, X]);
+        }
+
+        #[test]
+        fn it_should_score_chained_merges() {
+            let rules = Rules::classic().with_chain_merges(true);
+            let source_field = field("2,2,4./..../..../....");
+
+            let (new_field, outcome) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert_eq!(new_field[0], [EIGHT, TWO, X, X]);
+            assert_eq!(outcome.points, 4 + 8);
+        }
+
+        #[test]
+        fn it_should_detect_the_configured_target() {
+            let rules = Rules::classic().with_target(8);
+            let source_field = field("4,4../..../..../....");
+
+            let (_, outcome) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert!(outcome.won);
+        }
+
+        #[test]
+        fn it_should_not_win_with_a_target_which_was_on_the_board() {
+            let rules = Rules::classic().with_target(8);
+            let source_field = field("8.../..../..../...2");
+
+            let (_, outcome) = move_field_with(source_field, Direction::Left, &rules);
+
+            assert!(outcome.moved);
+            assert!(!outcome.won);
         }
     }
 
@@ -332,22 +429,18 @@ mod tests {
         use crate::game_actions::tests::*;
 
         #[test]
-        fn it_should_initialize_the_board() {
-            #[rustfmt::skip]
-            let source_field = [
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ];
-            let new_field = initialize_field(source_field, 0.0, 0.0);
-            #[rustfmt::skip]
-            assert_eq!(new_field, [
-                [TWO, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X],
-                [X, X, X, X]
-            ]);
+        fn it_should_place_the_starting_tiles() {
+            let new_field =
+                initialize_field(EMPTY_FIELD, &Rules::classic(), &mut StepRng::new(0, 0));
+            assert_eq!(new_field, field("2,2../..../..../...."));
+        }
+
+        #[test]
+        fn it_should_place_the_configured_number_of_starting_tiles() {
+            let rules = Rules::classic().with_starting_tiles(5);
+            let new_field = initialize_field(EMPTY_FIELD, &rules, &mut StepRng::new(0, 0));
+            assert_eq!(new_field[0], [TWO; 4]);
+            assert_eq!(new_field[1], [TWO, X, X, X]);
         }
     }
 }
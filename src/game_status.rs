@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fmt;
+
+/// The tile value which wins the game unless configured otherwise.
+pub const DEFAULT_TARGET: u64 = 2048;
+
+/// ```text
+/// Playing --target reached--> Won --continue_after_win--> ContinuePlaying
+///    |                                                          |
+///    +--------------------no move left---------> Lost <---------+
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameStatus {
+    Playing,
+    /// The target tile was reached. Moves are rejected until
+    /// [`Game::continue_after_win`](crate::game::Game::continue_after_win)
+    /// is called.
+    Won,
+    ContinuePlaying,
+    Lost,
+}
+
+impl GameStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, GameStatus::Won | GameStatus::Lost)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameError {
+    /// The game is lost, no move is possible anymore.
+    GameOver,
+    /// The game is won and has to be continued explicitly before moving.
+    GameWon,
+    /// Only a won game can be continued.
+    NotWon,
+    NothingToUndo,
+    NothingToRedo,
+    /// All undo tokens of the game are used up.
+    NoUndoTokensLeft,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::GameWon => write!(f, "the game is won, continue it to keep moving"),
+            GameError::NotWon => write!(f, "only a won game can be continued"),
+            GameError::NothingToUndo => write!(f, "there is no move to undo"),
+            GameError::NothingToRedo => write!(f, "there is no move to redo"),
+            GameError::NoUndoTokensLeft => write!(f, "no undo is left"),
+        }
+    }
+}
+
+impl Error for GameError {}
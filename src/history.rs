@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+/// How many moves can be undone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryDepth {
+    Limited(usize),
+    Unlimited,
+}
+
+/// Undo and redo stacks of snapshots. Once the undo stack is full, the
+/// oldest snapshot is dropped.
+#[derive(Clone, Debug)]
+pub(crate) struct History<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    depth: HistoryDepth,
+}
+
+impl<T> History<T> {
+    pub(crate) fn new(depth: HistoryDepth) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth,
+        }
+    }
+
+    /// Remembers the state before a move. A new move makes the undone moves
+    /// unreachable.
+    pub(crate) fn record(&mut self, snapshot: T) {
+        self.redo.clear();
+        if !self.is_recording() {
+            return;
+        }
+        if let HistoryDepth::Limited(depth) = self.depth {
+            if self.undo.len() == depth {
+                self.undo.pop_front();
+            }
+        }
+        self.undo.push_back(snapshot);
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.depth != HistoryDepth::Limited(0)
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns the previous snapshot and keeps `current` for a redo.
+    pub(crate) fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Returns the snapshot before the last undo and keeps `current` for
+    /// another undo.
+    pub(crate) fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::*;
+
+    #[test]
+    fn it_should_undo_and_redo_in_order() {
+        let mut history = History::new(HistoryDepth::Unlimited);
+        history.record(0);
+        history.record(1);
+
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.undo(0), None);
+        assert_eq!(history.redo(0), Some(1));
+        assert_eq!(history.redo(1), Some(2));
+        assert_eq!(history.redo(2), None);
+    }
+
+    #[test]
+    fn it_should_drop_the_oldest_snapshot_beyond_the_depth() {
+        let mut history = History::new(HistoryDepth::Limited(2));
+        history.record(0);
+        history.record(1);
+        history.record(2);
+
+        assert_eq!(history.undo(3), Some(2));
+        assert_eq!(history.undo(2), Some(1));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn it_should_record_nothing_without_depth() {
+        let mut history = History::new(HistoryDepth::Limited(0));
+        history.record(0);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn it_should_forget_undone_snapshots_after_a_new_move() {
+        let mut history = History::new(HistoryDepth::Unlimited);
+        history.record(0);
+        history.undo(1);
+
+        history.record(0);
+
+        assert!(!history.can_redo());
+    }
+}
@@ -1,11 +1,27 @@
+#[cfg(feature = "bitboard")]
+pub mod bitboard;
+pub mod ai;
+pub mod board;
+pub mod board_value;
+pub mod direction;
+pub mod env;
 pub mod game;
+pub mod game_status;
 
-mod board_value;
 mod field;
 mod field_add_random_value;
 mod field_move_and_merge;
-mod field_rotate;
 mod field_gameover;
 mod game_actions;
+pub mod history;
+pub mod move_outcome;
+pub mod notation;
+pub mod ntuple;
+pub mod render;
+pub mod rules;
+#[cfg(feature = "serde")]
+pub mod save;
 mod score_calculator;
 mod random;
+pub mod tile_spawner;
+pub mod tile_tracking;
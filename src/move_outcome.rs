@@ -0,0 +1,40 @@
+use crate::board::Coordinate;
+use crate::game::ExternalScore;
+use crate::tile_tracking::TileEvent;
+
+/// Two tiles which were merged into one by a move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Merge {
+    /// The cells of both tiles before the move.
+    pub sources: [Coordinate; 2],
+    /// The cell of the merged tile after the move.
+    pub target: Coordinate,
+    pub value: u64,
+}
+
+/// A tile which was added to the board after a move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SpawnedTile {
+    pub cell: Coordinate,
+    pub value: u64,
+}
+
+/// Everything that happened during a single move.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MoveOutcome {
+    /// Whether the move changed the board. A move which changes nothing does
+    /// not spawn a tile either, unless the rules say otherwise.
+    pub moved: bool,
+    pub points: ExternalScore,
+    pub merges: Vec<Merge>,
+    /// The tiles added after the move, one with the classic rules.
+    pub spawned: Vec<SpawnedTile>,
+    /// Whether a merge of the move created the target tile of the rules, 2048
+    /// by default. A target tile which was on the board before does not
+    /// count.
+    pub won: bool,
+    /// Whether no further move is possible.
+    pub lost: bool,
+    /// The movement of every tile, only filled if tile tracking is enabled.
+    pub events: Vec<TileEvent>,
+}
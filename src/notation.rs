@@ -0,0 +1,384 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, BoardError};
+
+/// Who acts next in a [`Position`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Side {
+    /// The player moves next, written as `p`.
+    #[default]
+    Player,
+    /// A tile spawns next, written as `s`, e.g. right after a move.
+    Spawn,
+}
+
+/// A board in a compact one-line notation together with optional metadata,
+/// e.g. `2.4./..../8.../...2 s 1024`.
+///
+/// The rows are separated by `/` from top to bottom. Every `.` is an empty
+/// cell and every number a tile; two adjacent tiles are separated by `,`,
+/// like `2,2..`, and a `,` anywhere else is an error. The board may be
+/// followed by the side to act, `p` or `s`, and the score, both separated by
+/// a space.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Position<const W: usize = 4, const H: usize = W> {
+    pub board: Board<W, H>,
+    pub side: Side,
+    pub score: Option<u64>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NotationError {
+    /// The number of rows does not match the board height.
+    WrongRowCount {
+        expected: usize,
+        actual: usize,
+    },
+    /// The number of cells in the row does not match the board width.
+    WrongRowLength {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A character which is neither a digit, `.` nor `,`.
+    InvalidCharacter(char),
+    /// A `,` which does not separate two tiles, in the given row.
+    MisplacedComma(String),
+    /// The digits of a tile, too many for a `u64`.
+    ValueTooLarge(String),
+    InvalidBoard(BoardError),
+    /// The side is neither `p` nor `s`.
+    InvalidSide(String),
+    InvalidScore(String),
+    /// More text follows the score.
+    TrailingText(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::WrongRowCount { expected, actual } => {
+                write!(f, "expected {} rows but got {}", expected, actual)
+            }
+            NotationError::WrongRowLength {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {} cells in row {} but got {}",
+                expected, row, actual
+            ),
+            NotationError::InvalidCharacter(character) => {
+                write!(f, "{:?} is not allowed in a row", character)
+            }
+            NotationError::MisplacedComma(row) => {
+                write!(f, "a ',' must separate two tiles in {:?}", row)
+            }
+            NotationError::ValueTooLarge(digits) => write!(f, "{} is too large for a tile", digits),
+            NotationError::InvalidBoard(error) => error.fmt(f),
+            NotationError::InvalidSide(side) => write!(f, "{:?} is not a side", side),
+            NotationError::InvalidScore(score) => write!(f, "{:?} is not a score", score),
+            NotationError::TrailingText(text) => write!(f, "unexpected {:?}", text),
+        }
+    }
+}
+
+impl Error for NotationError {}
+
+impl From<BoardError> for NotationError {
+    fn from(error: BoardError) -> Self {
+        NotationError::InvalidBoard(error)
+    }
+}
+
+impl<const W: usize, const H: usize> Board<W, H> {
+    /// Parses the board part of the notation of a [`Position`], e.g.
+    /// `2.4./..../8.../...2`.
+    pub fn from_notation(notation: &str) -> Result<Self, NotationError> {
+        let rows = notation.split('/').collect::<Vec<&str>>();
+        if rows.len() != H {
+            return Err(NotationError::WrongRowCount {
+                expected: H,
+                actual: rows.len(),
+            });
+        }
+        let mut values = [[0; W]; H];
+        for (index, row) in rows.into_iter().enumerate() {
+            let cells = parse_row(row)?;
+            if cells.len() != W {
+                return Err(NotationError::WrongRowLength {
+                    row: index,
+                    expected: W,
+                    actual: cells.len(),
+                });
+            }
+            values[index].copy_from_slice(&cells);
+        }
+        Ok(Board::from_values(values)?)
+    }
+
+    pub fn to_notation(&self) -> String {
+        let rows = self.to_values().map(|row| {
+            let mut text = String::new();
+            let mut previous_was_tile = false;
+            for value in row {
+                match value {
+                    0 => text.push('.'),
+                    _ if previous_was_tile => text.push_str(&format!(",{}", value)),
+                    _ => text.push_str(&value.to_string()),
+                }
+                previous_was_tile = value != 0;
+            }
+            text
+        });
+        rows.join("/")
+    }
+}
+
+fn parse_row(row: &str) -> Result<Vec<u64>, NotationError> {
+    let misplaced_comma = || NotationError::MisplacedComma(row.to_string());
+    let mut cells = Vec::new();
+    let mut digits = String::new();
+    let mut after_comma = false;
+    for character in row.chars() {
+        match character {
+            '.' if after_comma => return Err(misplaced_comma()),
+            '.' => {
+                push_tile(&mut cells, &mut digits)?;
+                cells.push(0);
+            }
+            ',' if digits.is_empty() => return Err(misplaced_comma()),
+            ',' => {
+                push_tile(&mut cells, &mut digits)?;
+                after_comma = true;
+            }
+            '0'..='9' => {
+                digits.push(character);
+                after_comma = false;
+            }
+            _ => return Err(NotationError::InvalidCharacter(character)),
+        }
+    }
+    if after_comma {
+        return Err(misplaced_comma());
+    }
+    push_tile(&mut cells, &mut digits)?;
+    Ok(cells)
+}
+
+/// Adds the tile of the digits read so far, if any, and clears them.
+fn push_tile(cells: &mut Vec<u64>, digits: &mut String) -> Result<(), NotationError> {
+    if !digits.is_empty() {
+        let value = digits
+            .parse()
+            .map_err(|_| NotationError::ValueTooLarge(digits.clone()))?;
+        cells.push(value);
+        digits.clear();
+    }
+    Ok(())
+}
+
+impl<const W: usize, const H: usize> Position<W, H> {
+    /// A position where the player moves next and no score is known.
+    pub fn new(board: Board<W, H>) -> Self {
+        Self {
+            board,
+            side: Side::Player,
+            score: None,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> FromStr for Position<W, H> {
+    type Err = NotationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut tokens = text.split_whitespace();
+        let board = Board::from_notation(tokens.next().unwrap_or(""))?;
+        let side = match tokens.next() {
+            None | Some("p") => Side::Player,
+            Some("s") => Side::Spawn,
+            Some(side) => return Err(NotationError::InvalidSide(side.to_string())),
+        };
+        let score = tokens
+            .next()
+            .map(|score| {
+                score
+                    .parse::<u64>()
+                    .map_err(|_| NotationError::InvalidScore(score.to_string()))
+            })
+            .transpose()?;
+        if let Some(token) = tokens.next() {
+            return Err(NotationError::TrailingText(token.to_string()));
+        }
+        Ok(Self { board, side, score })
+    }
+}
+
+/// Writes the notation parsed by [`Position::from_str`], leaving out the
+/// metadata if it has its default values.
+impl<const W: usize, const H: usize> fmt::Display for Position<W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.board.to_notation())?;
+        if self.side != Side::Player || self.score.is_some() {
+            let side = match self.side {
+                Side::Player => 'p',
+                Side::Spawn => 's',
+            };
+            write!(f, " {}", side)?;
+        }
+        if let Some(score) = self.score {
+            write!(f, " {}", score)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notation::*;
+
+    fn board(values: [[u64; 4]; 4]) -> Board {
+        Board::from_values(values).unwrap()
+    }
+
+    #[test]
+    fn it_should_parse_a_board() {
+        assert_eq!(
+            Board::from_notation("2.4./..../8.../...2"),
+            Ok(board([[2, 0, 4, 0], [0; 4], [8, 0, 0, 0], [0, 0, 0, 2]]))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_adjacent_and_large_tiles() {
+        assert_eq!(
+            Board::from_notation("2,2,2048,4/..../..../16,32.."),
+            Ok(board([[2, 2, 2048, 4], [0; 4], [0; 4], [16, 32, 0, 0]]))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_other_sizes() {
+        let board = Board::<3, 2>::from_notation("2../.4,8").unwrap();
+        assert_eq!(board.to_values(), [[2, 0, 0], [0, 4, 8]]);
+    }
+
+    #[test]
+    fn it_should_write_the_notation_of_a_board() {
+        let board = board([[2, 2, 0, 4], [0; 4], [0, 1 << 40, 0, 0], [0, 0, 0, 2]]);
+        assert_eq!(board.to_notation(), "2,2.4/..../.1099511627776../...2");
+    }
+
+    #[test]
+    fn it_should_round_trip_a_board() {
+        let board = board([[2, 4, 8, 16], [0, 32, 0, 64], [128, 0, 0, 0], [0; 4]]);
+        assert_eq!(Board::from_notation(&board.to_notation()), Ok(board));
+    }
+
+    #[test]
+    fn it_should_reject_a_wrong_number_of_rows() {
+        assert_eq!(
+            Board::<4>::from_notation("..../...."),
+            Err(NotationError::WrongRowCount {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_reject_a_row_of_the_wrong_length() {
+        assert_eq!(
+            Board::<4>::from_notation("..../2.../.../...."),
+            Err(NotationError::WrongRowLength {
+                row: 2,
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_tiles() {
+        assert_eq!(
+            Board::<4>::from_notation("22.../..../..../...."),
+            Err(NotationError::InvalidBoard(BoardError::InvalidValue(22)))
+        );
+        assert_eq!(
+            Board::<4>::from_notation("x.../..../..../...."),
+            Err(NotationError::InvalidCharacter('x'))
+        );
+    }
+
+    #[test]
+    fn it_should_report_the_digits_of_a_tile_which_is_too_large() {
+        assert_eq!(
+            Board::<4>::from_notation("99999999999999999999.../..../..../...."),
+            Err(NotationError::ValueTooLarge(
+                "99999999999999999999".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_commas_which_do_not_separate_tiles() {
+        for (notation, row) in [
+            (",2,2../..../..../....", ",2,2.."),
+            ("2,,2../..../..../....", "2,,2.."),
+            ("2,.../..../..../....", "2,..."),
+            ("..../.,2../..../....", ".,2.."),
+            ("..../..../..../...2,", "...2,"),
+        ] {
+            assert_eq!(
+                Board::<4>::from_notation(notation),
+                Err(NotationError::MisplacedComma(row.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_parse_the_metadata_of_a_position() {
+        let position: Position = "2.4./..../8.../...2 s 1024".parse().unwrap();
+        assert_eq!(position.side, Side::Spawn);
+        assert_eq!(position.score, Some(1024));
+        assert_eq!(position.board.get((0, 2)), Some(4));
+    }
+
+    #[test]
+    fn it_should_default_the_metadata_of_a_position() {
+        let position: Position = "2.4./..../8.../...2".parse().unwrap();
+        assert_eq!(position, Position::new(position.board));
+    }
+
+    #[test]
+    fn it_should_round_trip_a_position() {
+        for notation in [
+            "2.4./..../8.../...2",
+            "2.4./..../8.../...2 s",
+            "..../..../..../..2,4 p 12",
+        ] {
+            let position: Position = notation.parse().unwrap();
+            assert_eq!(position.to_string(), notation);
+        }
+    }
+
+    #[test]
+    fn it_should_reject_invalid_metadata() {
+        assert_eq!(
+            "..../..../..../.... x".parse::<Position>(),
+            Err(NotationError::InvalidSide("x".to_string()))
+        );
+        assert_eq!(
+            "..../..../..../.... p -4".parse::<Position>(),
+            Err(NotationError::InvalidScore("-4".to_string()))
+        );
+        assert_eq!(
+            "..../..../..../.... p 4 more".parse::<Position>(),
+            Err(NotationError::TrailingText("more".to_string()))
+        );
+    }
+}
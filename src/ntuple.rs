@@ -0,0 +1,534 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use rand::Rng;
+
+use crate::ai::Heuristic;
+use crate::board::{Board, Coordinate};
+use crate::board_value::BoardValue;
+use crate::direction::Direction;
+use crate::field::Field;
+use crate::field_move_and_merge::move_and_merge_traced;
+use crate::game::ExternalScore;
+use crate::game_actions::initialize_field;
+use crate::random::random;
+use crate::rules::Rules;
+use crate::score_calculator::calculate_points;
+use crate::tile_spawner::TileSpawner;
+
+/// The version of the weight files written by [`NTupleNetwork::save_to`].
+pub const WEIGHTS_VERSION: u32 = 1;
+
+/// The most cells of a pattern. A pattern of six cells already needs a table
+/// of `16^6` weights.
+pub const MAX_PATTERN_LENGTH: usize = 6;
+
+/// The most patterns of a network.
+pub const MAX_PATTERNS: usize = 64;
+
+/// The most weights of all patterns together, enough for eight patterns of
+/// six cells in 512 MiB. This bounds the memory a weight file can ask for.
+pub const MAX_WEIGHTS: usize = 8 * 16_usize.pow(6);
+
+const MAGIC: &[u8; 4] = b"NTUP";
+const SIZE: usize = 4;
+/// Every cell of a pattern takes one of this many states: empty or one of
+/// the exponents up to `15`. Larger exponents share the state of `15`.
+const STATES: usize = 16;
+/// The number of weights read from a weight file at once.
+const WEIGHTS_PER_READ: usize = 4096;
+
+/// A pattern with the cells of its lookup table, e.g. the four cells of the
+/// top row.
+pub type Pattern = Vec<Coordinate>;
+
+/// A value function for the classic 4x4 board: the sum of one weight per
+/// pattern and board symmetry, looked up by the tiles on the cells of the
+/// pattern. Every pattern is applied to all 8 rotations and reflections of
+/// the board, which share its lookup table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NTupleNetwork {
+    patterns: Vec<Pattern>,
+    /// The flat cell indices of every pattern in each of the 8 symmetries.
+    symmetric_cells: Vec<[Vec<usize>; 8]>,
+    weights: Vec<Vec<f32>>,
+}
+
+#[derive(Debug)]
+pub enum NTupleError {
+    Io(io::Error),
+    /// The pattern is empty, longer than [`MAX_PATTERN_LENGTH`], repeats a
+    /// cell or has a cell outside of the board.
+    InvalidPattern(Pattern),
+    /// There are more patterns than [`MAX_PATTERNS`].
+    TooManyPatterns(usize),
+    /// The patterns need more weights than [`MAX_WEIGHTS`].
+    TooManyWeights(usize),
+    /// The data does not start like a weight file.
+    NotAWeightFile,
+    /// The weights were written by a newer version of the crate.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for NTupleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NTupleError::Io(error) => error.fmt(f),
+            NTupleError::InvalidPattern(pattern) => {
+                write!(f, "{:?} is not a valid pattern", pattern)
+            }
+            NTupleError::TooManyPatterns(count) => {
+                write!(f, "{} patterns are more than {}", count, MAX_PATTERNS)
+            }
+            NTupleError::TooManyWeights(count) => {
+                write!(f, "{} weights are more than {}", count, MAX_WEIGHTS)
+            }
+            NTupleError::NotAWeightFile => write!(f, "the data is not a weight file"),
+            NTupleError::UnsupportedVersion(version) => {
+                write!(f, "the weight file version {} is not supported", version)
+            }
+        }
+    }
+}
+
+impl Error for NTupleError {}
+
+impl From<io::Error> for NTupleError {
+    fn from(error: io::Error) -> Self {
+        NTupleError::Io(error)
+    }
+}
+
+impl NTupleNetwork {
+    /// A network with all weights `0`.
+    pub fn new(patterns: impl IntoIterator<Item = Pattern>) -> Result<Self, NTupleError> {
+        let patterns = patterns.into_iter().collect::<Vec<Pattern>>();
+        validate_all(&patterns)?;
+        let weights = patterns
+            .iter()
+            .map(|pattern| vec![0.0; table_size(pattern)])
+            .collect();
+        Ok(Self::with_weights(patterns, weights))
+    }
+
+    /// A network of valid patterns with a table of the right size for each.
+    fn with_weights(patterns: Vec<Pattern>, weights: Vec<Vec<f32>>) -> Self {
+        let symmetric_cells = patterns
+            .iter()
+            .map(|pattern| {
+                SYMMETRIES.map(|symmetry| {
+                    pattern
+                        .iter()
+                        .map(|&cell| {
+                            let (row, column) = symmetry(cell);
+                            row * SIZE + column
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        Self {
+            patterns,
+            symmetric_cells,
+            weights,
+        }
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// The estimated points still to come from the board on.
+    pub fn value(&self, board: &Board) -> f32 {
+        self.value_of(board.field())
+    }
+
+    /// The move with the most points plus value of the board after it, no
+    /// tile spawned yet. `None` if no move is left.
+    pub fn best_move(&self, board: Board) -> Option<Direction> {
+        self.choose_move(board.field())
+            .map(|(direction, _, _)| direction)
+    }
+
+    /// Plays one game with the classic rules, always taking
+    /// [`NTupleNetwork::best_move`], and learns from it with TD(0) on the
+    /// boards after every move: the value of such a board is moved towards
+    /// the points of the next move plus the value of the board after it.
+    /// Returns the score of the game.
+    ///
+    /// The learning rate is shared by all weights of a board, `0.1` is a
+    /// common choice.
+    pub fn train_episode<R: Rng + ?Sized>(
+        &mut self,
+        learning_rate: f32,
+        rng: &mut R,
+    ) -> ExternalScore {
+        let rules = Rules::classic();
+        let mut spawner = rules.spawner();
+        let mut field = initialize_field([[BoardValue::EMPTY; SIZE]; SIZE], &rules, rng);
+        let mut score = 0;
+        let mut previous: Option<Field> = None;
+        while let Some((_, after, points)) = self.choose_move(field) {
+            if let Some(previous) = previous {
+                let target = points as f32 + self.value_of(after);
+                self.learn(previous, target, learning_rate);
+            }
+            score += points;
+            previous = Some(after);
+            let (value, position) = (random(rng), random(rng));
+            field = spawner.spawn(Board::from(after), value, position).field();
+        }
+        if let Some(previous) = previous {
+            // No points follow the last move
+            self.learn(previous, 0.0, learning_rate);
+        }
+        score
+    }
+
+    /// Writes the patterns and weights: the bytes `NTUP`, the version, the
+    /// number of patterns and for every pattern its length and cells, all
+    /// little endian, followed by the weights of all patterns as `f32`.
+    pub fn save_to(&self, mut writer: impl Write) -> Result<(), NTupleError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&WEIGHTS_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.patterns.len() as u32).to_le_bytes())?;
+        for pattern in self.patterns.iter() {
+            writer.write_all(&[pattern.len() as u8])?;
+            for (row, column) in pattern.iter() {
+                writer.write_all(&[*row as u8, *column as u8])?;
+            }
+        }
+        for weights in self.weights.iter() {
+            let bytes = weights
+                .iter()
+                .flat_map(|weight| weight.to_le_bytes())
+                .collect::<Vec<u8>>();
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a network written by [`NTupleNetwork::save_to`]. The weights are
+    /// read in chunks, so a file which is cut off fails before memory for
+    /// all of them is allocated.
+    pub fn load_from(mut reader: impl Read) -> Result<Self, NTupleError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(NTupleError::NotAWeightFile);
+        }
+        let version = read_u32(&mut reader)?;
+        if !(1..=WEIGHTS_VERSION).contains(&version) {
+            return Err(NTupleError::UnsupportedVersion(version));
+        }
+        let count = read_u32(&mut reader)? as usize;
+        if count > MAX_PATTERNS {
+            return Err(NTupleError::TooManyPatterns(count));
+        }
+        let mut patterns = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut length = [0; 1];
+            reader.read_exact(&mut length)?;
+            let mut cells = vec![0; 2 * length[0] as usize];
+            reader.read_exact(&mut cells)?;
+            let pattern = cells
+                .chunks_exact(2)
+                .map(|cell| (cell[0] as usize, cell[1] as usize))
+                .collect();
+            patterns.push(pattern);
+        }
+        validate_all(&patterns)?;
+        let mut weights = Vec::with_capacity(count);
+        let mut bytes = vec![0; 4 * WEIGHTS_PER_READ];
+        for pattern in patterns.iter() {
+            let total = table_size(pattern);
+            let mut pattern_weights = Vec::new();
+            while pattern_weights.len() < total {
+                let chunk = &mut bytes[..4 * WEIGHTS_PER_READ.min(total - pattern_weights.len())];
+                reader.read_exact(chunk)?;
+                pattern_weights.extend(
+                    chunk
+                        .chunks_exact(4)
+                        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+                );
+            }
+            weights.push(pattern_weights);
+        }
+        Ok(Self::with_weights(patterns, weights))
+    }
+
+    fn value_of(&self, field: Field) -> f32 {
+        let states = states(field);
+        self.symmetric_cells
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(symmetries, weights)| {
+                symmetries
+                    .iter()
+                    .map(|cells| weights[index(&states, cells)])
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    /// The best move with the board after it and its points.
+    fn choose_move(&self, field: Field) -> Option<(Direction, Field, ExternalScore)> {
+        let mut best: Option<(f32, Direction, Field, ExternalScore)> = None;
+        for direction in Direction::ALL {
+            let (after, merges, _) = move_and_merge_traced(field, direction, false);
+            if after == field {
+                continue;
+            }
+            let points = calculate_points(&merges);
+            let value = points as f32 + self.value_of(after);
+            if best.is_none_or(|(best_value, ..)| value > best_value) {
+                best = Some((value, direction, after, points));
+            }
+        }
+        best.map(|(_, direction, after, points)| (direction, after, points))
+    }
+
+    /// Moves the value of the field towards the target.
+    fn learn(&mut self, field: Field, target: f32, learning_rate: f32) {
+        let error = target - self.value_of(field);
+        let lookups = (self.patterns.len() * SYMMETRIES.len()) as f32;
+        let delta = learning_rate * error / lookups;
+        let states = states(field);
+        for (symmetries, weights) in self.symmetric_cells.iter().zip(self.weights.iter_mut()) {
+            for cells in symmetries.iter() {
+                weights[index(&states, cells)] += delta;
+            }
+        }
+    }
+}
+
+/// The two outer and the two inner rows and three squares of four cells.
+/// With the symmetries this covers every row, column and square of the
+/// board.
+impl Default for NTupleNetwork {
+    fn default() -> Self {
+        Self::new([
+            vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+            vec![(1, 0), (1, 1), (1, 2), (1, 3)],
+            vec![(0, 0), (0, 1), (1, 0), (1, 1)],
+            vec![(0, 1), (0, 2), (1, 1), (1, 2)],
+            vec![(1, 1), (1, 2), (2, 1), (2, 2)],
+        ])
+        .expect("the default patterns are valid")
+    }
+}
+
+/// Rates boards by their value, e.g. for an
+/// [`Expectimax`](crate::ai::Expectimax) search.
+impl Heuristic for NTupleNetwork {
+    fn evaluate(&self, board: &Board) -> f64 {
+        self.value(board) as f64
+    }
+}
+
+/// The identity, the three rotations and the four reflections.
+const SYMMETRIES: [fn(Coordinate) -> Coordinate; 8] = [
+    |(row, column)| (row, column),
+    |(row, column)| (column, SIZE - 1 - row),
+    |(row, column)| (SIZE - 1 - row, SIZE - 1 - column),
+    |(row, column)| (SIZE - 1 - column, row),
+    |(row, column)| (row, SIZE - 1 - column),
+    |(row, column)| (SIZE - 1 - row, column),
+    |(row, column)| (column, row),
+    |(row, column)| (SIZE - 1 - column, SIZE - 1 - row),
+];
+
+/// Checks every pattern and the number of patterns and weights.
+fn validate_all(patterns: &[Pattern]) -> Result<(), NTupleError> {
+    if patterns.len() > MAX_PATTERNS {
+        return Err(NTupleError::TooManyPatterns(patterns.len()));
+    }
+    for pattern in patterns {
+        validate(pattern)?;
+    }
+    let weights = patterns.iter().map(table_size).sum();
+    if weights > MAX_WEIGHTS {
+        return Err(NTupleError::TooManyWeights(weights));
+    }
+    Ok(())
+}
+
+fn table_size(pattern: &Pattern) -> usize {
+    STATES.pow(pattern.len() as u32)
+}
+
+fn validate(pattern: &Pattern) -> Result<(), NTupleError> {
+    let in_bounds = pattern
+        .iter()
+        .all(|(row, column)| *row < SIZE && *column < SIZE);
+    let unique = pattern
+        .iter()
+        .enumerate()
+        .all(|(index, cell)| !pattern[..index].contains(cell));
+    if pattern.is_empty() || pattern.len() > MAX_PATTERN_LENGTH || !in_bounds || !unique {
+        return Err(NTupleError::InvalidPattern(pattern.clone()));
+    }
+    Ok(())
+}
+
+/// The state of every cell, row by row.
+fn states(field: Field) -> [usize; SIZE * SIZE] {
+    let mut states = [0; SIZE * SIZE];
+    for (state, cell) in states.iter_mut().zip(field.iter().flatten()) {
+        *state = (cell.exponent() as usize).min(STATES - 1);
+    }
+    states
+}
+
+fn index(states: &[usize; SIZE * SIZE], cells: &[usize]) -> usize {
+    cells
+        .iter()
+        .fold(0, |index, cell| index * STATES + states[*cell])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameRng};
+    use crate::ntuple::*;
+    use rand::SeedableRng;
+
+    fn board(notation: &str) -> Board {
+        Board::from_notation(notation).unwrap()
+    }
+
+    fn trained_network(episodes: usize) -> NTupleNetwork {
+        let mut network = NTupleNetwork::default();
+        let mut rng = GameRng::seed_from_u64(1);
+        for _ in 0..episodes {
+            network.train_episode(0.1, &mut rng);
+        }
+        network
+    }
+
+    #[test]
+    fn it_should_reject_invalid_patterns() {
+        for pattern in [
+            vec![],
+            vec![(0, 0), (4, 0)],
+            vec![(0, 0), (0, 0)],
+            (0..7).map(|column| (column / 4, column % 4)).collect(),
+        ] {
+            assert!(matches!(
+                NTupleNetwork::new([pattern]),
+                Err(NTupleError::InvalidPattern(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_should_give_symmetric_boards_the_same_value() {
+        let network = trained_network(20);
+        let value = network.value(&board("2,4../8.../..../...."));
+        assert_ne!(value, 0.0);
+        for symmetric in [
+            "..4,2/...8/..../....",
+            "..../..../8.../2,4..",
+            "2,8../4.../..../....",
+        ] {
+            // The lookups are the same, only summed up in another order
+            assert!((network.value(&board(symmetric)) - value).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn it_should_learn_the_points_which_follow() {
+        let mut network = NTupleNetwork::default();
+        let field = board("2,2../..../..../....").field();
+        network.learn(field, 10.0, 0.1);
+        let value = network.value_of(field);
+        assert!(value > 0.0 && value < 10.0);
+        for _ in 0..100 {
+            network.learn(field, 10.0, 0.1);
+        }
+        assert!((network.value_of(field) - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn it_should_play_better_after_training() {
+        let play = |network: &NTupleNetwork| {
+            let mut game = Game::with_seed(7).with_target(u64::MAX);
+            while let Some(direction) = network.best_move(game.get_board()) {
+                game.make_move(direction).unwrap();
+            }
+            game.get_score()
+        };
+        let untrained = play(&NTupleNetwork::default());
+        assert!(play(&trained_network(100)) > untrained);
+    }
+
+    #[test]
+    fn it_should_find_no_move_on_a_lost_board() {
+        let network = NTupleNetwork::default();
+        assert_eq!(
+            network.best_move(board("2,4,2,4/4,2,4,2/2,4,2,4/4,2,4,2")),
+            None
+        );
+    }
+
+    #[test]
+    fn it_should_save_and_load_the_weights() {
+        let network = trained_network(5);
+        let mut bytes = Vec::new();
+        network.save_to(&mut bytes).unwrap();
+        assert_eq!(&bytes[..4], b"NTUP");
+        assert_eq!(NTupleNetwork::load_from(bytes.as_slice()).unwrap(), network);
+    }
+
+    #[test]
+    fn it_should_reject_other_files() {
+        assert!(matches!(
+            NTupleNetwork::load_from(&b"2048\x01\x00\x00\x00"[..]),
+            Err(NTupleError::NotAWeightFile)
+        ));
+        assert!(matches!(
+            NTupleNetwork::load_from(&b"NTUP\x02\x00\x00\x00"[..]),
+            Err(NTupleError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            NTupleNetwork::load_from(&b"NTUP\x01\x00"[..]),
+            Err(NTupleError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_check_the_patterns_of_a_file_before_reading_the_weights() {
+        let header = |count: u32, pattern: &[u8]| {
+            let mut bytes = b"NTUP\x01\x00\x00\x00".to_vec();
+            bytes.extend(count.to_le_bytes());
+            for _ in 0..count.min(MAX_PATTERNS as u32) {
+                bytes.extend(pattern);
+            }
+            bytes
+        };
+        let six_cells = [6, 0, 0, 0, 1, 0, 2, 0, 3, 1, 0, 1, 1];
+        assert!(matches!(
+            NTupleNetwork::load_from(header(u32::MAX, &six_cells).as_slice()),
+            Err(NTupleError::TooManyPatterns(_))
+        ));
+        assert!(matches!(
+            NTupleNetwork::load_from(header(1, &[1, 9, 9]).as_slice()),
+            Err(NTupleError::InvalidPattern(_))
+        ));
+        assert!(matches!(
+            NTupleNetwork::load_from(header(MAX_PATTERNS as u32, &six_cells).as_slice()),
+            Err(NTupleError::TooManyWeights(_))
+        ));
+        // Claims 8 tables of 16^6 weights, but has none of them
+        assert!(matches!(
+            NTupleNetwork::load_from(header(8, &six_cells).as_slice()),
+            Err(NTupleError::Io(_))
+        ));
+    }
+}
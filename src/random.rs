@@ -1,19 +1,23 @@
 extern crate rand;
 
+use rand::Rng;
+
 pub type RandomNumber = f32;
 
-pub fn random() -> RandomNumber {
-    rand::random()
+pub fn random<R: Rng + ?Sized>(rng: &mut R) -> RandomNumber {
+    rng.gen()
 }
 
 #[cfg(test)]
 mod tests {
 
     use crate::random::random;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     #[test]
     fn it_should_return_a_number_between_0_and_1_excluding() {
-        let r = random();
+        let r = random(&mut rand::thread_rng());
         assert!(r >= 0.0);
         assert!(r < 1.0);
     }
@@ -21,6 +25,16 @@ mod tests {
     #[test]
     fn it_should_seldom_return_same_numbers() {
         // This might fail since it is randome and this case can (rarely) happen
-        assert!(random() != random() || random() != random());
+        let mut rng = rand::thread_rng();
+        assert!(random(&mut rng) != random(&mut rng) || random(&mut rng) != random(&mut rng));
+    }
+
+    #[test]
+    fn it_should_return_the_same_numbers_for_the_same_seed() {
+        let mut first = StdRng::seed_from_u64(42);
+        let mut second = StdRng::seed_from_u64(42);
+        for _ in 0..10 {
+            assert_eq!(random(&mut first), random(&mut second));
+        }
     }
 }
@@ -0,0 +1,252 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::board::Board;
+use crate::game::Game;
+
+/// The characters the grid is drawn with.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Border {
+    /// `+`, `-` and `|`, readable everywhere.
+    #[default]
+    Ascii,
+    /// Box-drawing characters like `┌`, `─` and `│`.
+    Unicode,
+}
+
+/// How [`Board::display`] and [`Game::display`] draw the grid. The default is
+/// plain ASCII without colours, as used by the `Display` impls.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Style {
+    border: Border,
+    colors: bool,
+}
+
+impl Style {
+    pub fn ascii() -> Self {
+        Self::default()
+    }
+
+    pub fn unicode() -> Self {
+        Self {
+            border: Border::Unicode,
+            ..Self::default()
+        }
+    }
+
+    /// Colours every tile with an ANSI escape code depending on its value.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+}
+
+/// The characters of one kind of grid line: left end, filler, crossing and
+/// right end.
+struct Line([char; 4]);
+
+struct Charset {
+    top: Line,
+    middle: Line,
+    bottom: Line,
+    vertical: char,
+}
+
+const ASCII: Charset = Charset {
+    top: Line(['+', '-', '+', '+']),
+    middle: Line(['+', '-', '+', '+']),
+    bottom: Line(['+', '-', '+', '+']),
+    vertical: '|',
+};
+
+const UNICODE: Charset = Charset {
+    top: Line(['┌', '─', '┬', '┐']),
+    middle: Line(['├', '─', '┼', '┤']),
+    bottom: Line(['└', '─', '┴', '┘']),
+    vertical: '│',
+};
+
+/// The colours of the exponents `1` (the tile `2`) to `11` (the tile `2048`),
+/// see [`tile_color`].
+const PALETTE: [u8; 11] = [7, 3, 11, 1, 9, 5, 13, 4, 12, 6, 14];
+
+/// The colour of the tiles with the exponent, `1` for the tile `2`, as one of
+/// the 16 standard ANSI colours: `0` to `7` and their bright variants `8` to
+/// `15`. The colours repeat for tiles above `2048`. Front ends drawing their
+/// own boards use it to colour the tiles like [`Style::with_colors`].
+pub fn tile_color(exponent: u8) -> u8 {
+    PALETTE[(exponent as usize).saturating_sub(1) % PALETTE.len()]
+}
+
+/// Draws a board, see [`Board::display`].
+pub struct BoardDisplay<'a, const W: usize, const H: usize> {
+    board: &'a Board<W, H>,
+    style: Style,
+}
+
+/// Draws the score above the board of a game, see [`Game::display`].
+pub struct GameDisplay<'a, R, const W: usize, const H: usize> {
+    game: &'a Game<R, W, H>,
+    style: Style,
+}
+
+impl<const W: usize, const H: usize> Board<W, H> {
+    /// A grid with one line per row, every column as wide as the largest
+    /// tile. Empty cells are drawn as `.`.
+    pub fn display(&self, style: Style) -> BoardDisplay<'_, W, H> {
+        BoardDisplay { board: self, style }
+    }
+}
+
+impl<R: Rng + Clone, const W: usize, const H: usize> Game<R, W, H> {
+    pub fn display(&self, style: Style) -> GameDisplay<'_, R, W, H> {
+        GameDisplay { game: self, style }
+    }
+}
+
+impl<const W: usize, const H: usize> fmt::Display for BoardDisplay<'_, W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let charset = match self.style.border {
+            Border::Ascii => &ASCII,
+            Border::Unicode => &UNICODE,
+        };
+        let values = self.board.to_values();
+        let width = values
+            .iter()
+            .flatten()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(1);
+        write_line(f, &charset.top, W, width)?;
+        for (index, row) in values.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+                write_line(f, &charset.middle, W, width)?;
+            }
+            writeln!(f)?;
+            write!(f, "{}", charset.vertical)?;
+            for value in row {
+                write!(f, " ")?;
+                write_cell(f, *value, width, self.style.colors)?;
+                write!(f, " {}", charset.vertical)?;
+            }
+        }
+        writeln!(f)?;
+        write_line(f, &charset.bottom, W, width)
+    }
+}
+
+fn write_line(f: &mut fmt::Formatter, line: &Line, columns: usize, width: usize) -> fmt::Result {
+    let [left, filler, crossing, right] = line.0;
+    let cell = filler.to_string().repeat(width + 2);
+    let cells = vec![cell; columns].join(&crossing.to_string());
+    write!(f, "{}{}{}", left, cells, right)
+}
+
+fn write_cell(f: &mut fmt::Formatter, value: u64, width: usize, colors: bool) -> fmt::Result {
+    if value == 0 {
+        return write!(f, "{:>width$}", '.', width = width);
+    }
+    if !colors {
+        return write!(f, "{:>width$}", value, width = width);
+    }
+    let color = match tile_color(value.trailing_zeros() as u8) {
+        color @ 0..=7 => 30 + color,
+        bright => 90 + bright - 8,
+    };
+    // Tiles from 2048 on stand out in bold
+    let weight = if value >= 2048 { "1;" } else { "" };
+    write!(
+        f,
+        "\x1b[{}{}m{:>width$}\x1b[0m",
+        weight,
+        color,
+        value,
+        width = width
+    )
+}
+
+impl<R: Rng + Clone, const W: usize, const H: usize> fmt::Display for GameDisplay<'_, R, W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Score: {}", self.game.get_score())?;
+        write!(f, "{}", self.game.get_board().display(self.style))
+    }
+}
+
+impl<const W: usize, const H: usize> fmt::Display for Board<W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(Style::default()))
+    }
+}
+
+impl<R: Rng + Clone, const W: usize, const H: usize> fmt::Display for Game<R, W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(Style::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::*;
+
+    fn board() -> Board {
+        Board::from_notation("2.4./..../.2048../...2").unwrap()
+    }
+
+    #[test]
+    fn it_should_draw_an_aligned_ascii_grid() {
+        let expected = "\
++------+------+------+------+
+|    2 |    . |    4 |    . |
++------+------+------+------+
+|    . |    . |    . |    . |
++------+------+------+------+
+|    . | 2048 |    . |    . |
++------+------+------+------+
+|    . |    . |    . |    2 |
++------+------+------+------+";
+        assert_eq!(board().to_string(), expected);
+    }
+
+    #[test]
+    fn it_should_fit_the_columns_to_the_largest_tile() {
+        let board = Board::<2, 1>::from_values([[2, 4]]).unwrap();
+        assert_eq!(board.to_string(), "+---+---+\n| 2 | 4 |\n+---+---+");
+    }
+
+    #[test]
+    fn it_should_draw_box_drawing_characters() {
+        let board = Board::<2>::from_values([[2, 0], [0, 16]]).unwrap();
+        let expected = "\
+┌────┬────┐
+│  2 │  . │
+├────┼────┤
+│  . │ 16 │
+└────┴────┘";
+        assert_eq!(board.display(Style::unicode()).to_string(), expected);
+    }
+
+    #[test]
+    fn it_should_color_the_tiles() {
+        let board = Board::<2, 1>::from_values([[2, 2048]]).unwrap();
+        let text = board.display(Style::ascii().with_colors(true)).to_string();
+        assert!(text.contains("\x1b[37m   2\x1b[0m"));
+        assert!(text.contains("\x1b[1;96m2048\x1b[0m"));
+    }
+
+    #[test]
+    fn it_should_repeat_the_palette_for_large_tiles() {
+        assert_eq!(tile_color(1), 7);
+        assert_eq!(tile_color(11), 14);
+        assert_eq!(tile_color(12), tile_color(1));
+    }
+
+    #[test]
+    fn it_should_print_the_score_above_the_board() {
+        let game = Game::from_field(board());
+        let text = game.to_string();
+        assert!(text.starts_with("Score: 0\n+------+"));
+        assert!(text.ends_with(&board().to_string()));
+    }
+}
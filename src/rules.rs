@@ -0,0 +1,233 @@
+use crate::board::Board;
+use crate::direction::Direction;
+use crate::field_add_random_value::get_coordinates_of_empty_cells;
+use crate::field_move_and_merge::move_and_merge_traced;
+use crate::game_status::DEFAULT_TARGET;
+use crate::tile_spawner::{SpawnerError, WeightedSpawner};
+
+/// The rules of a game: how tiles spawn, how they merge and when the game is
+/// won. [`Rules::default`] are the rules of the classic game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    starting_tiles: usize,
+    spawns_per_move: usize,
+    spawn_weights: WeightedSpawner,
+    spawn_on_unchanged_move: bool,
+    chain_merges: bool,
+    target: u64,
+}
+
+impl Rules {
+    /// Two starting tiles, one new tile after every move which changed the
+    /// board, a `2` in 90% and a `4` in 10% of the cases, every tile merges
+    /// at most once per move and `2048` wins.
+    pub fn classic() -> Self {
+        Self {
+            starting_tiles: 2,
+            spawns_per_move: 1,
+            spawn_weights: WeightedSpawner::new([(2, 9.0), (4, 1.0)])
+                .expect("the classic weights are valid"),
+            spawn_on_unchanged_move: false,
+            chain_merges: false,
+            target: DEFAULT_TARGET,
+        }
+    }
+
+    /// The number of tiles on the board of a new game.
+    pub fn with_starting_tiles(mut self, count: usize) -> Self {
+        self.starting_tiles = count;
+        self
+    }
+
+    /// The number of tiles spawned after a move, as long as there is room.
+    pub fn with_spawns_per_move(mut self, count: usize) -> Self {
+        self.spawns_per_move = count;
+        self
+    }
+
+    /// `weights` are `(value, weight)` pairs like for a
+    /// [`WeightedSpawner`], e.g. `[(2, 9.0), (4, 1.0)]`.
+    pub fn with_spawn_weights(
+        mut self,
+        weights: impl IntoIterator<Item = (u64, f32)>,
+    ) -> Result<Self, SpawnerError> {
+        self.spawn_weights = WeightedSpawner::new(weights)?;
+        Ok(self)
+    }
+
+    /// Whether a move which changes nothing spawns tiles anyway.
+    pub fn with_spawn_on_unchanged_move(mut self, spawn: bool) -> Self {
+        self.spawn_on_unchanged_move = spawn;
+        self
+    }
+
+    /// Whether a merged tile may merge again in the same move, so that
+    /// `[2, 2, 4]` moves left to `[8]` instead of `[4, 4]`.
+    pub fn with_chain_merges(mut self, chain: bool) -> Self {
+        self.chain_merges = chain;
+        self
+    }
+
+    /// The tile value which wins the game.
+    pub fn with_target(mut self, target: u64) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn starting_tiles(&self) -> usize {
+        self.starting_tiles
+    }
+
+    pub fn spawns_per_move(&self) -> usize {
+        self.spawns_per_move
+    }
+
+    pub fn spawn_weights(&self) -> Vec<(u64, f32)> {
+        self.spawn_weights.weights()
+    }
+
+    pub fn spawns_on_unchanged_move(&self) -> bool {
+        self.spawn_on_unchanged_move
+    }
+
+    pub fn chain_merges(&self) -> bool {
+        self.chain_merges
+    }
+
+    pub fn target(&self) -> u64 {
+        self.target
+    }
+
+    /// The board after moving in the direction, before any tile spawns.
+    pub fn after_state<const W: usize, const H: usize>(
+        &self,
+        board: Board<W, H>,
+        direction: Direction,
+    ) -> Board<W, H> {
+        Board::from(move_and_merge_traced(board.field(), direction, self.chain_merges).0)
+    }
+
+    /// Every board the spawns after a move can lead to, with its probability.
+    /// The probabilities add up to `1`. With more than one spawn per move the
+    /// same board may be listed more than once, once for every order of
+    /// spawns leading to it. A full board stays as it is.
+    pub fn enumerate_spawns<const W: usize, const H: usize>(
+        &self,
+        board: Board<W, H>,
+    ) -> impl Iterator<Item = (Board<W, H>, f64)> {
+        let probabilities = self.spawn_weights.probabilities();
+        let mut outcomes = vec![(board.field(), 1.0)];
+        for _ in 0..self.spawns_per_move {
+            outcomes = outcomes
+                .into_iter()
+                .flat_map(|(field, probability)| {
+                    let empty_cells = get_coordinates_of_empty_cells(field);
+                    if empty_cells.is_empty() {
+                        return vec![(field, probability)];
+                    }
+                    let cell_probability = probability / empty_cells.len() as f64;
+                    let mut spawned = Vec::new();
+                    for (row, column) in empty_cells {
+                        for (value, value_probability) in probabilities.iter() {
+                            let mut field = field;
+                            field[row][column] = *value;
+                            spawned.push((field, cell_probability * value_probability));
+                        }
+                    }
+                    spawned
+                })
+                .collect();
+        }
+        outcomes
+            .into_iter()
+            .map(|(field, probability)| (Board::from(field), probability))
+    }
+
+    /// The spawner for the starting tiles and, unless the game replaces it,
+    /// for the tiles after every move.
+    pub(crate) fn spawner(&self) -> WeightedSpawner {
+        self.spawn_weights.clone()
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::classic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::BoardError;
+    use crate::rules::*;
+
+    #[test]
+    fn it_should_default_to_the_classic_rules() {
+        let rules = Rules::default();
+        assert_eq!(rules, Rules::classic());
+        assert_eq!(rules.starting_tiles(), 2);
+        assert_eq!(rules.spawns_per_move(), 1);
+        assert_eq!(rules.spawn_weights(), vec![(2, 9.0), (4, 1.0)]);
+        assert!(!rules.spawns_on_unchanged_move());
+        assert!(!rules.chain_merges());
+        assert_eq!(rules.target(), 2048);
+    }
+
+    #[test]
+    fn it_should_move_without_spawning() {
+        let board: Board = Board::from_notation("2,2,4./..../..../....").unwrap();
+        let moved = Rules::classic().after_state(board, Direction::Left);
+        assert_eq!(moved.to_notation(), "4,4../..../..../....");
+        let chained = Rules::classic().with_chain_merges(true);
+        let moved = chained.after_state(board, Direction::Left);
+        assert_eq!(moved.to_notation(), "8.../..../..../....");
+    }
+
+    #[test]
+    fn it_should_enumerate_every_spawn_with_its_probability() {
+        let board: Board = Board::from_notation("2,4,8,16/4,8,16,32/8,16,32,64/16,32..").unwrap();
+        let spawns: Vec<(Board, f64)> = Rules::classic().enumerate_spawns(board).collect();
+        assert_eq!(spawns.len(), 4);
+        let expected = [((3, 2), 2, 0.45), ((3, 2), 4, 0.05), ((3, 3), 2, 0.45)];
+        for (cell, value, probability) in expected {
+            let spawned = board.with_tile(cell, value).unwrap();
+            let (_, actual) = spawns.iter().find(|(board, _)| *board == spawned).unwrap();
+            assert!((actual - probability).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_enumerate_several_spawns_per_move() {
+        let board = Board::<2>::from_values([[2, 0], [0, 0]]).unwrap();
+        let rules = Rules::classic()
+            .with_spawns_per_move(2)
+            .with_spawn_weights([(2, 1.0)])
+            .unwrap();
+        let spawns: Vec<(Board<2>, f64)> = rules.enumerate_spawns(board).collect();
+        assert_eq!(spawns.len(), 6);
+        let total: f64 = spawns.iter().map(|(_, probability)| probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(spawns
+            .iter()
+            .all(|(board, _)| board.empty_cells().len() == 1));
+    }
+
+    #[test]
+    fn it_should_keep_a_full_board_when_enumerating_spawns() {
+        let board: Board = Board::from_notation("2,4,2,4/4,2,4,2/2,4,2,4/4,2,4,2").unwrap();
+        let spawns: Vec<(Board, f64)> = Rules::classic().enumerate_spawns(board).collect();
+        assert_eq!(spawns, vec![(board, 1.0)]);
+    }
+
+    #[test]
+    fn it_should_reject_invalid_spawn_weights() {
+        assert_eq!(
+            Rules::classic().with_spawn_weights([(6, 1.0)]),
+            Err(SpawnerError::InvalidBoard(BoardError::InvalidValue(6)))
+        );
+        assert_eq!(
+            Rules::classic().with_spawn_weights([]),
+            Err(SpawnerError::NoPositiveWeight)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use rand::Rng;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::board::{Board, BoardError};
+use crate::game::Game;
+use crate::game_status::GameStatus;
+use crate::rules::Rules;
+use crate::tile_spawner::SpawnerError;
+
+/// The version of the save format written by this version of the crate.
+/// Saves of a newer version are rejected.
+pub const SAVE_VERSION: u32 = 1;
+
+/// The first bytes of every binary save.
+const MAGIC: &[u8; 4] = b"2048";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SaveFormat {
+    /// A JSON object with a `version` field.
+    Json,
+    /// The bytes `2048`, the version as a little endian `u32` and the game
+    /// encoded with bincode.
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    /// The data does not start like a binary save.
+    NotASave,
+    /// The save was written by a newer version of the crate.
+    UnsupportedVersion(u32),
+    /// The board of the save does not have the size of the game.
+    SizeMismatch {
+        width: usize,
+        height: usize,
+    },
+    InvalidBoard(BoardError),
+    InvalidRules(SpawnerError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => error.fmt(f),
+            SaveError::Json(error) => error.fmt(f),
+            SaveError::Binary(error) => error.fmt(f),
+            SaveError::NotASave => write!(f, "the data is not a saved game"),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "the save version {} is not supported", version)
+            }
+            SaveError::SizeMismatch { width, height } => {
+                write!(f, "the saved board is {}x{}", width, height)
+            }
+            SaveError::InvalidBoard(error) => error.fmt(f),
+            SaveError::InvalidRules(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Json(error)
+    }
+}
+
+impl From<bincode::Error> for SaveError {
+    fn from(error: bincode::Error) -> Self {
+        SaveError::Binary(error)
+    }
+}
+
+impl From<BoardError> for SaveError {
+    fn from(error: BoardError) -> Self {
+        SaveError::InvalidBoard(error)
+    }
+}
+
+impl From<SpawnerError> for SaveError {
+    fn from(error: SpawnerError) -> Self {
+        SaveError::InvalidRules(error)
+    }
+}
+
+/// The saved state of a game. The history, the tile ids and a spawner set
+/// with [`Game::with_spawner`] are not part of it.
+#[derive(Deserialize, Serialize)]
+struct SavedGame<R> {
+    version: u32,
+    width: usize,
+    height: usize,
+    /// The cells in row-major order, `0` for an empty cell.
+    tiles: Vec<u64>,
+    score: u64,
+    moves: u64,
+    rng: R,
+    rules: SavedRules,
+    status: GameStatus,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SavedRules {
+    starting_tiles: usize,
+    spawns_per_move: usize,
+    spawn_weights: Vec<(u64, f32)>,
+    spawn_on_unchanged_move: bool,
+    chain_merges: bool,
+    target: u64,
+}
+
+/// Read before the rest of a JSON save, so that newer versions are rejected
+/// before their fields are parsed.
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u32,
+}
+
+impl<'a, R: Rng + Clone, const W: usize, const H: usize> From<&'a Game<R, W, H>>
+    for SavedGame<&'a R>
+{
+    fn from(game: &'a Game<R, W, H>) -> Self {
+        let rules = game.rules();
+        Self {
+            version: SAVE_VERSION,
+            width: W,
+            height: H,
+            tiles: game.get_field().values,
+            score: game.get_score(),
+            moves: game.get_move_count(),
+            rng: game.rng(),
+            rules: SavedRules {
+                starting_tiles: rules.starting_tiles(),
+                spawns_per_move: rules.spawns_per_move(),
+                spawn_weights: rules.spawn_weights(),
+                spawn_on_unchanged_move: rules.spawns_on_unchanged_move(),
+                chain_merges: rules.chain_merges(),
+                target: rules.target(),
+            },
+            status: game.status(),
+        }
+    }
+}
+
+impl<R: Rng + Clone> SavedGame<R> {
+    fn into_game<const W: usize, const H: usize>(self) -> Result<Game<R, W, H>, SaveError> {
+        check_version(self.version)?;
+        if self.width != W || self.height != H {
+            return Err(SaveError::SizeMismatch {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let board = Board::<W, H>::from_row_major(&self.tiles)?;
+        let rules = Rules::classic()
+            .with_starting_tiles(self.rules.starting_tiles)
+            .with_spawns_per_move(self.rules.spawns_per_move)
+            .with_spawn_weights(self.rules.spawn_weights)?
+            .with_spawn_on_unchanged_move(self.rules.spawn_on_unchanged_move)
+            .with_chain_merges(self.rules.chain_merges)
+            .with_target(self.rules.target);
+        Ok(
+            Game::from_field_with_rules_and_rng(board, rules, self.rng).with_progress(
+                self.score,
+                self.moves,
+                self.status,
+            ),
+        )
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SaveError> {
+    if (1..=SAVE_VERSION).contains(&version) {
+        Ok(())
+    } else {
+        Err(SaveError::UnsupportedVersion(version))
+    }
+}
+
+impl<R: Rng + Clone + Serialize, const W: usize, const H: usize> Serialize for Game<R, W, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedGame::from(self).serialize(serializer)
+    }
+}
+
+impl<'de, R: Rng + Clone + Deserialize<'de>, const W: usize, const H: usize> Deserialize<'de>
+    for Game<R, W, H>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SavedGame::<R>::deserialize(deserializer)?
+            .into_game()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl<R: Rng + Clone + Serialize + DeserializeOwned, const W: usize, const H: usize> Game<R, W, H> {
+    /// Writes the board, the score, the move count, the state of the random
+    /// number generator, the rules and the status.
+    pub fn save_to(&self, mut writer: impl Write, format: SaveFormat) -> Result<(), SaveError> {
+        match format {
+            SaveFormat::Json => serde_json::to_writer(writer, self)?,
+            SaveFormat::Binary => {
+                writer.write_all(MAGIC)?;
+                writer.write_all(&SAVE_VERSION.to_le_bytes())?;
+                bincode::serialize_into(writer, self)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Continues a game saved with [`Game::save_to`]. The same moves result
+    /// in the same tiles as in the saved game.
+    pub fn load_from(mut reader: impl Read, format: SaveFormat) -> Result<Self, SaveError> {
+        let saved: SavedGame<R> = match format {
+            SaveFormat::Json => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                check_version(serde_json::from_slice::<VersionHeader>(&bytes)?.version)?;
+                serde_json::from_slice(&bytes)?
+            }
+            SaveFormat::Binary => {
+                let mut header = [0; 8];
+                reader.read_exact(&mut header)?;
+                if header[..4] != MAGIC[..] {
+                    return Err(SaveError::NotASave);
+                }
+                check_version(u32::from_le_bytes([
+                    header[4], header[5], header[6], header[7],
+                ]))?;
+                bincode::deserialize_from(reader)?
+            }
+        };
+        saved.into_game()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::GameRng;
+    use crate::history::HistoryDepth;
+    use crate::save::*;
+    use rand::SeedableRng;
+
+    fn played_game() -> Game {
+        let rules = Rules::classic().with_spawns_per_move(2).with_target(4096);
+        let mut game = Game::with_rules_and_rng(rules, GameRng::seed_from_u64(2048));
+        for _ in 0..5 {
+            game.move_left().ok();
+            game.move_down().ok();
+        }
+        game
+    }
+
+    fn save(game: &Game, format: SaveFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        game.save_to(&mut bytes, format).unwrap();
+        bytes
+    }
+
+    fn assert_same_game(loaded: &mut Game, original: &mut Game) {
+        assert_eq!(loaded.get_board(), original.get_board());
+        assert_eq!(loaded.get_score(), original.get_score());
+        assert_eq!(loaded.get_move_count(), original.get_move_count());
+        assert_eq!(loaded.status(), original.status());
+        assert_eq!(loaded.rules(), original.rules());
+        // The random number generator continues where it was saved
+        for _ in 0..5 {
+            assert_eq!(loaded.move_up().ok(), original.move_up().ok());
+            assert_eq!(loaded.move_right().ok(), original.move_right().ok());
+        }
+        assert_eq!(loaded.get_board(), original.get_board());
+    }
+
+    #[test]
+    fn it_should_load_a_json_save() {
+        let mut game = played_game();
+        let bytes = save(&game, SaveFormat::Json);
+
+        let mut loaded = Game::load_from(bytes.as_slice(), SaveFormat::Json).unwrap();
+
+        assert_same_game(&mut loaded, &mut game);
+    }
+
+    #[test]
+    fn it_should_load_a_binary_save() {
+        let mut game = played_game();
+        let bytes = save(&game, SaveFormat::Binary);
+
+        let mut loaded = Game::load_from(bytes.as_slice(), SaveFormat::Binary).unwrap();
+
+        assert_same_game(&mut loaded, &mut game);
+        assert!(bytes.len() < save(&game, SaveFormat::Json).len());
+    }
+
+    #[test]
+    fn it_should_serialize_a_game_with_serde() {
+        let mut game = played_game().with_history(HistoryDepth::Unlimited);
+        let json = serde_json::to_string(&game).unwrap();
+
+        let mut loaded: Game = serde_json::from_str(&json).unwrap();
+
+        assert_same_game(&mut loaded, &mut game);
+    }
+
+    #[test]
+    fn it_should_reject_a_json_save_of_a_newer_version() {
+        let json = String::from_utf8(save(&played_game(), SaveFormat::Json)).unwrap();
+        let newer = json.replace("\"version\":1", "\"version\":2");
+
+        let result = Game::<GameRng>::load_from(newer.as_bytes(), SaveFormat::Json);
+
+        assert!(matches!(result, Err(SaveError::UnsupportedVersion(2))));
+    }
+
+    #[test]
+    fn it_should_reject_a_binary_save_of_a_newer_version() {
+        let mut bytes = save(&played_game(), SaveFormat::Binary);
+        bytes[4] = 2;
+
+        let result = Game::<GameRng>::load_from(bytes.as_slice(), SaveFormat::Binary);
+
+        assert!(matches!(result, Err(SaveError::UnsupportedVersion(2))));
+    }
+
+    #[test]
+    fn it_should_reject_data_which_is_not_a_binary_save() {
+        let result = Game::<GameRng>::load_from(&b"PNG image"[..], SaveFormat::Binary);
+        assert!(matches!(result, Err(SaveError::NotASave)));
+    }
+
+    #[test]
+    fn it_should_reject_a_truncated_binary_save() {
+        let bytes = save(&played_game(), SaveFormat::Binary);
+
+        let result = Game::<GameRng>::load_from(&bytes[..bytes.len() / 2], SaveFormat::Binary);
+
+        assert!(matches!(result, Err(SaveError::Binary(_))));
+    }
+
+    #[test]
+    fn it_should_reject_corrupt_json() {
+        let result = Game::<GameRng>::load_from(&b"{\"version\":1,"[..], SaveFormat::Json);
+        assert!(matches!(result, Err(SaveError::Json(_))));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_tiles() {
+        let bytes = save(&played_game(), SaveFormat::Json);
+        let mut json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        json["tiles"][0] = 3.into();
+
+        let result = Game::<GameRng>::load_from(json.to_string().as_bytes(), SaveFormat::Json);
+
+        assert!(matches!(
+            result,
+            Err(SaveError::InvalidBoard(BoardError::InvalidValue(3)))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_rules() {
+        let bytes = save(&played_game(), SaveFormat::Json);
+        let mut json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        json["rules"]["spawn_weights"] = serde_json::json!([]);
+
+        let result = Game::<GameRng>::load_from(json.to_string().as_bytes(), SaveFormat::Json);
+
+        assert!(matches!(
+            result,
+            Err(SaveError::InvalidRules(SpawnerError::NoPositiveWeight))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_a_save_of_another_size() {
+        let bytes = save(&played_game(), SaveFormat::Binary);
+
+        let result = Game::<GameRng, 3>::load_from(bytes.as_slice(), SaveFormat::Binary);
+
+        assert!(matches!(
+            result,
+            Err(SaveError::SizeMismatch {
+                width: 4,
+                height: 4
+            })
+        ));
+    }
+}
@@ -1,104 +1,41 @@
-use crate::field::Row;
-use crate::field::Field;
-use crate::board_value::BoardValue;
-use crate::field_rotate::*;
+use crate::move_outcome::Merge;
 
 pub type Score = u64;
 
-pub fn calculate_added_points_left(from: Field, to: Field) -> Score {
-    calculate_added_points_per_row_left(from[0], to[0])
-        + calculate_added_points_per_row_left(from[1], to[1])
-        + calculate_added_points_per_row_left(from[2], to[2])
-        + calculate_added_points_per_row_left(from[3], to[3])
-}
-
-pub fn calculate_added_points_up(from: Field, to: Field) -> Score {
-    calculate_added_points_left(rotate_counterclockwise(from), rotate_counterclockwise(to))
-}
-
-pub fn calculate_added_points_right(from: Field, to: Field) -> Score {
-    calculate_added_points_left(
-        rotate_clockwise(rotate_clockwise(from)),
-        rotate_clockwise(rotate_clockwise(to)),
-    )
-}
-
-pub fn calculate_added_points_down(from: Field, to: Field) -> Score {
-    calculate_added_points_left(rotate_clockwise(from), rotate_clockwise(to))
-}
-
-pub fn calculate_added_points_per_row_left(from: Row, to: Row) -> Score {
-    let mut sum: Score = 0;
-    for merged_value in get_merged_values(from, to).iter() {
-        sum += merged_value.get_value() as Score
-    }
-    sum
-}
-
-fn get_merged_values(from: Row, to: Row) -> Vec<BoardValue> {
-    let from_vector = get_vector_without_zeros_sorted_descending(from);
-    let to_vector = get_vector_without_zeros_sorted_descending(to);
-
-    let mut from_iterator = from_vector.iter();
-
-    let mut merged_values: Vec<BoardValue> = Vec::new();
-    for current_to in to_vector.iter() {
-        let current_from = from_iterator.next();
-        if current_from.unwrap() == current_to {
-            continue;
-        }
-        from_iterator.next();
-        merged_values.push(*current_to);
-    }
-    merged_values
-}
-
-fn get_vector_without_zeros(row: Row) -> Vec<BoardValue> {
-    let mut result_vector: Vec<BoardValue> = Vec::new();
-    for index in 0..4 {
-        if row[index] != BoardValue::new(0) {
-            result_vector.push(row[index])
-        }
-    }
-    result_vector
-}
-
-fn get_vector_without_zeros_sorted_descending(row: Row) -> Vec<BoardValue> {
-    let mut values = get_vector_without_zeros(row);
-    values.sort_by(|a, b| b.cmp(a));
-    values
+/// The points of a move: the sum of the values of all merged tiles.
+pub fn calculate_points(merges: &[Merge]) -> Score {
+    merges.iter().map(|merge| merge.value as Score).sum()
 }
 
 #[cfg(test)]
-mod tests {
-    use crate::score_calculator::*;
+pub mod tests {
+    use crate::direction::Direction;
     use crate::field::tests::*;
+    use crate::field::Field;
+    use crate::field::Row;
+    use crate::field_move_and_merge::move_and_merge_traced;
+    use crate::score_calculator::*;
+
+    /// The points of moving `field`, for checking other engines against this one.
+    pub fn calculate_added_points<const W: usize, const H: usize>(
+        field: Field<W, H>,
+        direction: Direction,
+    ) -> Score {
+        calculate_points(&move_and_merge_traced(field, direction, false).1)
+    }
 
     #[cfg(test)]
     mod tests_calculate_added_points_down {
         use crate::score_calculator::tests::*;
         #[test]
-        fn it_should_return_zero_if_fields_are_equal() {
-            assert_eq!(calculate_added_points_down(EMPTY_FIELD, EMPTY_FIELD), 0);
+        fn it_should_return_zero_for_an_empty_field() {
+            assert_eq!(calculate_added_points(EMPTY_FIELD, Direction::Down), 0);
         }
 
         #[test]
         fn it_should_calculate_all_rows() {
             assert_eq!(
-                calculate_added_points_down(
-                    [
-                        [TWO, TWO, X, X],
-                        [X, TWO, X, X],
-                        [X, X, X, X],
-                        [TWO, TWO, TWO, TWO]
-                    ],
-                    [
-                        [X, X, X, X],
-                        [X, X, X, X],
-                        [X, TWO, X, X],
-                        [FOUR, FOUR, TWO, TWO] // 4    4   0   0
-                    ]
-                ),
+                calculate_added_points(field("2,2../.2../..../2,2,2,2"), Direction::Down),
                 4 + 4
             );
         }
@@ -108,28 +45,14 @@ mod tests {
     mod tests_calculate_added_points_up {
         use crate::score_calculator::tests::*;
         #[test]
-        fn it_should_return_zero_if_fields_are_equal() {
-            assert_eq!(calculate_added_points_up(EMPTY_FIELD, EMPTY_FIELD), 0);
+        fn it_should_return_zero_for_an_empty_field() {
+            assert_eq!(calculate_added_points(EMPTY_FIELD, Direction::Up), 0);
         }
 
         #[test]
         fn it_should_calculate_all_rows() {
             assert_eq!(
-                calculate_added_points_up(
-                    [
-                        [TWO, TWO, TWO, TWO],
-                        [X, X, X, X],
-                        [X, TWO, X, X],
-                        [TWO, TWO, X, X]
-                    ],
-                    [
-                        // 4    4   0   0
-                        [FOUR, FOUR, TWO, TWO],
-                        [X, TWO, X, X],
-                        [X, X, X, X],
-                        [X, X, X, X]
-                    ]
-                ),
+                calculate_added_points(field("2,2,2,2/..../.2../2,2.."), Direction::Up),
                 4 + 4
             );
         }
@@ -139,27 +62,14 @@ mod tests {
     mod tests_calculate_added_points_right {
         use crate::score_calculator::tests::*;
         #[test]
-        fn it_should_return_zero_if_fields_are_equal() {
-            assert_eq!(calculate_added_points_right(EMPTY_FIELD, EMPTY_FIELD), 0);
+        fn it_should_return_zero_for_an_empty_field() {
+            assert_eq!(calculate_added_points(EMPTY_FIELD, Direction::Right), 0);
         }
 
         #[test]
         fn it_should_calculate_all_rows() {
             assert_eq!(
-                calculate_added_points_right(
-                    [
-                        [TWO, TWO, TWO, TWO],
-                        [X, X, X, X],
-                        [X, TWO, X, X],
-                        [TWO, TWO, X, X]
-                    ],
-                    [
-                        [X, X, FOUR, FOUR], // 8 points
-                        [X, X, X, X],       // 0 points
-                        [X, X, X, TWO],     // 0 points
-                        [X, X, X, FOUR]     // 4 points
-                    ]
-                ),
+                calculate_added_points(field("2,2,2,2/..../.2../2,2.."), Direction::Right),
                 8 + 4
             );
         }
@@ -169,27 +79,14 @@ mod tests {
     mod tests_calculate_added_points_left {
         use crate::score_calculator::tests::*;
         #[test]
-        fn it_should_return_zero_if_fields_are_equal() {
-            assert_eq!(calculate_added_points_left(EMPTY_FIELD, EMPTY_FIELD), 0);
+        fn it_should_return_zero_for_an_empty_field() {
+            assert_eq!(calculate_added_points(EMPTY_FIELD, Direction::Left), 0);
         }
 
         #[test]
         fn it_should_calculate_all_rows() {
             assert_eq!(
-                calculate_added_points_left(
-                    [
-                        [TWO, TWO, TWO, TWO],
-                        [X, X, X, X],
-                        [X, TWO, X, X],
-                        [TWO, TWO, X, X]
-                    ],
-                    [
-                        [FOUR, FOUR, X, X], // 8 points
-                        [X, X, X, X],       // 0 points
-                        [TWO, X, X, X],     // 0 points
-                        [FOUR, X, X, X]     // 4 points
-                    ]
-                ),
+                calculate_added_points(field("2,2,2,2/..../.2../2,2.."), Direction::Left),
                 8 + 4
             );
         }
@@ -200,114 +97,107 @@ mod tests {
         use crate::score_calculator::tests::*;
         const EMPTY_ROW: Row = [X, X, X, X];
         const ROW_WITH_TWO_LEFT: Row = [TWO, X, X, X];
+
+        fn calculate_points_of_row(row: Row) -> Score {
+            calculate_added_points([row], Direction::Left)
+        }
+
         #[test]
-        fn it_should_return_zero_if_rows_are_equal() {
-            assert_eq!(calculate_added_points_per_row_left(EMPTY_ROW, EMPTY_ROW), 0);
+        fn it_should_return_zero_for_an_empty_row() {
+            assert_eq!(calculate_points_of_row(EMPTY_ROW), 0);
         }
 
         #[test]
-        fn it_should_return_zero_if_non_empty_rows_are_equal() {
-            assert_eq!(
-                calculate_added_points_per_row_left(ROW_WITH_TWO_LEFT, ROW_WITH_TWO_LEFT),
-                0
-            );
+        fn it_should_return_zero_if_a_single_tile_does_not_move() {
+            assert_eq!(calculate_points_of_row(ROW_WITH_TWO_LEFT), 0);
         }
 
         #[test]
         fn it_should_return_four_if_two_twos_are_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left([TWO, TWO, X, X], [FOUR, X, X, X]),
-                4
-            );
+            assert_eq!(calculate_points_of_row([TWO, TWO, X, X]), 4);
         }
 
         #[test]
         fn it_should_return_eight_if_four_twos_are_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left([TWO, TWO, TWO, TWO], [FOUR, FOUR, X, X]),
-                8
-            );
+            assert_eq!(calculate_points_of_row([TWO, TWO, TWO, TWO]), 8);
         }
 
         #[test]
         fn it_should_return_zero_if_nothing_was_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left([TWO, FOUR, X, X], [TWO, FOUR, X, X]),
-                0
-            );
+            assert_eq!(calculate_points_of_row([TWO, FOUR, X, X]), 0);
         }
 
         #[test]
         fn it_should_return_four_last_two_twos_are_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left(
-                    [EIGHT, FOUR, TWO, TWO],
-                    [EIGHT, FOUR, FOUR, X]
-                ),
-                4
-            );
+            assert_eq!(calculate_points_of_row([EIGHT, FOUR, TWO, TWO]), 4);
         }
         #[test]
         fn it_should_return_zero_if_two_was_just_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, X, X, TWO], [TWO, X, X, X]),
-                0
-            );
+            assert_eq!(calculate_points_of_row([X, X, X, TWO]), 0);
         }
         #[test]
         fn it_should_return_zero_if_four_was_just_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, X, TWO, FOUR], [TWO, FOUR, X, X]),
-                0
-            );
+            assert_eq!(calculate_points_of_row([X, X, TWO, FOUR]), 0);
         }
 
         #[test]
         fn it_should_return_zero_if_eight_was_just_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, TWO, FOUR, EIGHT], [TWO, FOUR, EIGHT, X]),
-                0
-            );
+            assert_eq!(calculate_points_of_row([X, TWO, FOUR, EIGHT]), 0);
         }
 
         #[test]
         fn it_should_return_four_if_two_twos_are_moved_and_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, TWO, X, TWO], [FOUR, X, X, X]),
-                4
-            );
+            assert_eq!(calculate_points_of_row([X, TWO, X, TWO]), 4);
         }
 
         #[test]
         fn it_should_return_four_if_two_twos_are_merged_and_one_is_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, TWO, TWO, TWO], [FOUR, TWO, X, X]),
-                4
-            );
+            assert_eq!(calculate_points_of_row([X, TWO, TWO, TWO]), 4);
         }
 
         #[test]
         fn it_should_return_four_if_two_twos_are_merged_and_one_four_is_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([X, TWO, TWO, FOUR], [FOUR, FOUR, X, X]),
-                4
-            );
+            assert_eq!(calculate_points_of_row([X, TWO, TWO, FOUR]), 4);
         }
 
         #[test]
         fn it_should_return_four_if_two_fours_are_merged_and_two_twos_are_moved() {
-            assert_eq!(
-                calculate_added_points_per_row_left([TWO, FOUR, FOUR, TWO], [TWO, EIGHT, TWO, X]),
-                8
-            );
+            assert_eq!(calculate_points_of_row([TWO, FOUR, FOUR, TWO]), 8);
         }
 
         #[test]
         fn it_should_return_four_if_two_fours_and_two_twos_are_merged() {
-            assert_eq!(
-                calculate_added_points_per_row_left([FOUR, FOUR, TWO, TWO], [EIGHT, FOUR, X, X]),
-                12
-            );
+            assert_eq!(calculate_points_of_row([FOUR, FOUR, TWO, TWO]), 12);
         }
     }
+
+    #[test]
+    fn it_should_calculate_the_points_of_a_larger_field() {
+        let field = [[TWO, TWO, FOUR, FOUR, X], [X; 5], [X; 5], [X; 5], [X; 5]];
+        assert_eq!(calculate_added_points(field, Direction::Left), 12);
+    }
+
+    #[test]
+    fn it_should_calculate_the_points_of_a_rectangular_field() {
+        let field = [[TWO, FOUR], [TWO, FOUR], [X, X]];
+        assert_eq!(calculate_added_points(field, Direction::Up), 12);
+    }
+
+    #[test]
+    fn it_should_sum_the_values_of_all_merges() {
+        let merges = [
+            Merge {
+                sources: [(0, 0), (0, 1)],
+                target: (0, 0),
+                value: 4,
+            },
+            Merge {
+                sources: [(1, 2), (1, 3)],
+                target: (1, 0),
+                value: 1 << 40,
+            },
+        ];
+        assert_eq!(calculate_points(&merges), 4 + (1 << 40));
+        assert_eq!(calculate_points(&[]), 0);
+    }
 }
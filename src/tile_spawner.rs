@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::board::{to_board_value, Board, BoardError, Coordinate};
+use crate::board_value::BoardValue;
+use crate::direction::Direction;
+use crate::field::Field;
+use crate::field_add_random_value::{add_given_value, add_value};
+use crate::field_move_and_merge::move_and_merge;
+use crate::random::RandomNumber;
+
+/// Places new tiles on the board after a move changed it.
+///
+/// The random numbers are in `[0, 1)`; spawners which do not need
+/// randomness are free to ignore them.
+pub trait TileSpawner<const W: usize = 4, const H: usize = W> {
+    fn spawn(
+        &mut self,
+        board: Board<W, H>,
+        random_number_value: RandomNumber,
+        random_number_position: RandomNumber,
+    ) -> Board<W, H>;
+}
+
+/// The classic spawn: a random empty cell gets a `2` in 90% and a `4` in 10%
+/// of the cases.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniformSpawner;
+
+impl<const W: usize, const H: usize> TileSpawner<W, H> for UniformSpawner {
+    fn spawn(
+        &mut self,
+        board: Board<W, H>,
+        random_number_value: RandomNumber,
+        random_number_position: RandomNumber,
+    ) -> Board<W, H> {
+        add_value(board.field(), random_number_value, random_number_position)
+            .map_or(board, Board::from)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SpawnerError {
+    InvalidBoard(BoardError),
+    /// No weights were given or all of them are zero.
+    NoPositiveWeight,
+    /// A weight is infinite or not a number, or the weights add up to
+    /// infinity.
+    NonFiniteWeight,
+}
+
+impl fmt::Display for SpawnerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpawnerError::InvalidBoard(error) => error.fmt(f),
+            SpawnerError::NoPositiveWeight => write!(f, "at least one weight must be positive"),
+            SpawnerError::NonFiniteWeight => write!(f, "the weights must be finite"),
+        }
+    }
+}
+
+impl Error for SpawnerError {}
+
+impl From<BoardError> for SpawnerError {
+    fn from(error: BoardError) -> Self {
+        SpawnerError::InvalidBoard(error)
+    }
+}
+
+/// Spawns the given values on a random empty cell, each chosen with a
+/// probability proportional to its weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedSpawner {
+    weights: Vec<(BoardValue, f32)>,
+}
+
+impl WeightedSpawner {
+    /// `weights` are `(value, weight)` pairs, e.g. `[(2, 9.0), (4, 1.0)]`.
+    pub fn new(weights: impl IntoIterator<Item = (u64, f32)>) -> Result<Self, SpawnerError> {
+        let weights = weights.into_iter().collect::<Vec<(u64, f32)>>();
+        if weights.iter().any(|(_, weight)| !weight.is_finite()) {
+            return Err(SpawnerError::NonFiniteWeight);
+        }
+        let weights = weights
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(value, weight)| Ok((to_board_value(value)?, weight)))
+            .collect::<Result<Vec<(BoardValue, f32)>, BoardError>>()?;
+        if weights.is_empty() {
+            return Err(SpawnerError::NoPositiveWeight);
+        }
+        if !weights.iter().map(|(_, weight)| weight).sum::<f32>().is_finite() {
+            return Err(SpawnerError::NonFiniteWeight);
+        }
+        Ok(Self { weights })
+    }
+
+    /// The `(value, weight)` pairs with a positive weight.
+    pub fn weights(&self) -> Vec<(u64, f32)> {
+        self.weights
+            .iter()
+            .map(|(value, weight)| (value.value(), *weight))
+            .collect()
+    }
+
+    /// Every value with the probability of spawning it, adding up to `1`.
+    pub(crate) fn probabilities(&self) -> Vec<(BoardValue, f64)> {
+        let total: f64 = self.weights.iter().map(|(_, weight)| *weight as f64).sum();
+        self.weights
+            .iter()
+            .map(|(value, weight)| (*value, *weight as f64 / total))
+            .collect()
+    }
+
+    fn get_board_value(&self, random_number: RandomNumber) -> BoardValue {
+        let total: f32 = self.weights.iter().map(|(_, weight)| weight).sum();
+        let mut threshold = random_number * total;
+        for (value, weight) in self.weights.iter() {
+            if threshold < *weight {
+                return *value;
+            }
+            threshold -= weight;
+        }
+        // Only reachable through rounding errors for a random number close to 1
+        self.weights[self.weights.len() - 1].0
+    }
+}
+
+impl<const W: usize, const H: usize> TileSpawner<W, H> for WeightedSpawner {
+    fn spawn(
+        &mut self,
+        board: Board<W, H>,
+        random_number_value: RandomNumber,
+        random_number_position: RandomNumber,
+    ) -> Board<W, H> {
+        add_given_value(
+            board.field(),
+            self.get_board_value(random_number_value),
+            random_number_position,
+        )
+        .map_or(board, Board::from)
+    }
+}
+
+/// An adversarial spawner: it places the tile in the cell and with the value
+/// that leaves the player with the fewest empty cells after their best move.
+/// Spawns which leave no possible move at all are preferred.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EvilSpawner;
+
+impl EvilSpawner {
+    const VALUES: [BoardValue; 2] = [BoardValue::new(2), BoardValue::new(4)];
+
+    fn rate_for_player<const W: usize, const H: usize>(field: Field<W, H>) -> i32 {
+        Direction::ALL
+            .iter()
+            .map(|direction| move_and_merge(field, *direction))
+            .filter(|moved| *moved != field)
+            .map(|moved| Board::from(moved).empty_cells().len() as i32)
+            .max()
+            .unwrap_or(-1)
+    }
+}
+
+impl<const W: usize, const H: usize> TileSpawner<W, H> for EvilSpawner {
+    fn spawn(&mut self, board: Board<W, H>, _: RandomNumber, _: RandomNumber) -> Board<W, H> {
+        let mut worst: Option<(i32, Field<W, H>)> = None;
+        for (row, column) in board.empty_cells() {
+            for value in Self::VALUES {
+                let mut field = board.field();
+                field[row][column] = value;
+                let rating = Self::rate_for_player(field);
+                if worst.is_none_or(|(worst_rating, _)| rating < worst_rating) {
+                    worst = Some((rating, field));
+                }
+            }
+        }
+        worst.map_or(board, |(_, field)| Board::from(field))
+    }
+}
+
+/// Replays a fixed list of tiles, one per spawn. Tiles whose cell is already
+/// occupied are skipped; once the list is used up nothing is spawned anymore.
+#[derive(Clone, Debug)]
+pub struct ScriptedSpawner<const W: usize = 4, const H: usize = W> {
+    tiles: VecDeque<(Coordinate, u64)>,
+}
+
+impl<const W: usize, const H: usize> ScriptedSpawner<W, H> {
+    pub fn new(tiles: impl IntoIterator<Item = (Coordinate, u64)>) -> Result<Self, SpawnerError> {
+        let empty = Board::<W, H>::from_values([[0; W]; H])?;
+        let tiles = tiles.into_iter().collect::<VecDeque<(Coordinate, u64)>>();
+        for (coordinate, value) in tiles.iter() {
+            empty.with_tile(*coordinate, *value)?;
+        }
+        Ok(Self { tiles })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+impl<const W: usize, const H: usize> TileSpawner<W, H> for ScriptedSpawner<W, H> {
+    fn spawn(&mut self, board: Board<W, H>, _: RandomNumber, _: RandomNumber) -> Board<W, H> {
+        match self.tiles.pop_front() {
+            Some((coordinate, value)) if board.get(coordinate) == Some(0) => {
+                board.with_tile(coordinate, value).unwrap_or(board)
+            }
+            _ => board,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field::tests::*;
+    use crate::tile_spawner::*;
+
+    #[cfg(test)]
+    mod uniform_spawner {
+        use crate::tile_spawner::tests::*;
+
+        #[test]
+        fn it_should_spawn_like_add_value() {
+            let board = UniformSpawner.spawn(Board::from(EMPTY_FIELD), 0.95, 0.0);
+            assert_eq!(
+                board.field(),
+                [[FOUR, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, X]]
+            );
+        }
+
+        #[test]
+        fn it_should_not_change_a_full_board() {
+            let full = Board::from([[TWO, FOUR, TWO, FOUR]; 4]);
+            assert_eq!(UniformSpawner.spawn(full, 0.0, 0.0), full);
+        }
+    }
+
+    #[cfg(test)]
+    mod weighted_spawner {
+        use crate::tile_spawner::tests::*;
+
+        #[test]
+        fn it_should_pick_values_according_to_the_weights() {
+            let mut spawner = WeightedSpawner::new([(2, 1.0), (8, 3.0)]).unwrap();
+
+            let low = spawner.spawn(Board::from(EMPTY_FIELD), 0.2, 0.0);
+            let high = spawner.spawn(Board::from(EMPTY_FIELD), 0.3, 0.0);
+
+            assert_eq!(low.get((0, 0)), Some(2));
+            assert_eq!(high.get((0, 0)), Some(8));
+        }
+
+        #[test]
+        fn it_should_ignore_values_without_weight() {
+            let mut spawner = WeightedSpawner::new([(2, 0.0), (4, 1.0)]).unwrap();
+            let board = spawner.spawn(Board::from(EMPTY_FIELD), 0.0, 0.0);
+            assert_eq!(board.get((0, 0)), Some(4));
+        }
+
+        #[test]
+        fn it_should_reject_invalid_values() {
+            assert_eq!(
+                WeightedSpawner::new([(3, 1.0)]).unwrap_err(),
+                SpawnerError::InvalidBoard(BoardError::InvalidValue(3))
+            );
+        }
+
+        #[test]
+        fn it_should_reject_weights_without_positive_weight() {
+            assert_eq!(
+                WeightedSpawner::new([(2, 0.0)]).unwrap_err(),
+                SpawnerError::NoPositiveWeight
+            );
+        }
+
+        #[test]
+        fn it_should_reject_weights_which_are_not_finite() {
+            for weights in [
+                vec![(2, f32::INFINITY)],
+                vec![(2, 9.0), (4, f32::NAN)],
+                vec![(2, f32::MAX), (4, f32::MAX)],
+            ] {
+                assert_eq!(
+                    WeightedSpawner::new(weights).unwrap_err(),
+                    SpawnerError::NonFiniteWeight
+                );
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod evil_spawner {
+        use crate::tile_spawner::tests::*;
+
+        #[test]
+        fn it_should_prefer_a_spawn_which_ends_the_game() {
+            #[rustfmt::skip]
+            let board = Board::from([
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, X],
+            ]);
+
+            let spawned = EvilSpawner.spawn(board, 0.0, 0.0);
+
+            assert_eq!(spawned.get((3, 3)), Some(2));
+        }
+
+        #[test]
+        fn it_should_pick_the_spawn_leaving_the_fewest_empty_cells() {
+            #[rustfmt::skip]
+            let board = Board::from([
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, FOUR, TWO],
+                [TWO, FOUR, TWO, FOUR],
+                [FOUR, TWO, X, X],
+            ]);
+
+            let spawned = EvilSpawner.spawn(board, 0.0, 0.0);
+
+            assert_eq!(spawned.get((3, 2)), Some(4));
+            assert_eq!(spawned.get((3, 3)), Some(0));
+        }
+    }
+
+    #[cfg(test)]
+    mod scripted_spawner {
+        use crate::tile_spawner::tests::*;
+
+        #[test]
+        fn it_should_replay_the_given_tiles_in_order() {
+            let mut spawner = ScriptedSpawner::new([((3, 3), 2), ((0, 1), 4)]).unwrap();
+
+            let first = spawner.spawn(Board::from(EMPTY_FIELD), 0.0, 0.0);
+            let second = spawner.spawn(first, 0.0, 0.0);
+
+            assert_eq!(second.get((3, 3)), Some(2));
+            assert_eq!(second.get((0, 1)), Some(4));
+            assert_eq!(second.empty_cells().len(), 14);
+            assert_eq!(spawner.remaining(), 0);
+        }
+
+        #[test]
+        fn it_should_spawn_nothing_once_the_script_is_used_up() {
+            let mut spawner = ScriptedSpawner::new([]).unwrap();
+            let board = Board::from(EMPTY_FIELD);
+            assert_eq!(spawner.spawn(board, 0.0, 0.0), board);
+        }
+
+        #[test]
+        fn it_should_reject_tiles_outside_of_the_board() {
+            assert_eq!(
+                ScriptedSpawner::<4>::new([((0, 4), 2)]).unwrap_err(),
+                SpawnerError::InvalidBoard(BoardError::OutOfBounds((0, 4)))
+            );
+        }
+    }
+}
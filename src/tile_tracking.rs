@@ -0,0 +1,203 @@
+use crate::board::Coordinate;
+use crate::field::Field;
+use crate::field_move_and_merge::CellEvent;
+use crate::move_outcome::SpawnedTile;
+
+/// A stable identity of a tile, which stays the same while the tile moves.
+pub type TileId = u64;
+
+pub type TileIds<const W: usize = 4, const H: usize = W> = [[Option<TileId>; W]; H];
+
+/// What happened to a single tile during a move, in the order of the steps
+/// of the move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TileEvent {
+    TileMoved {
+        id: TileId,
+        from: Coordinate,
+        to: Coordinate,
+    },
+    /// Both tiles merge into `into`. The merged tile keeps the first id,
+    /// the second tile disappears.
+    TilesMerged {
+        ids: [TileId; 2],
+        into: Coordinate,
+        value: u64,
+    },
+    TileSpawned {
+        id: TileId,
+        at: Coordinate,
+        value: u64,
+    },
+}
+
+/// Keeps track of the id of every tile on the field.
+#[derive(Clone, Debug)]
+pub(crate) struct TileTracker<const W: usize = 4, const H: usize = W> {
+    ids: TileIds<W, H>,
+    next_id: TileId,
+}
+
+impl<const W: usize, const H: usize> TileTracker<W, H> {
+    /// Assigns a new id to every tile on the field.
+    pub(crate) fn new(field: Field<W, H>) -> Self {
+        let mut tracker = Self {
+            ids: [[None; W]; H],
+            next_id: 0,
+        };
+        for (row, cells) in field.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                if cell.value() != 0 {
+                    tracker.ids[row][column] = Some(tracker.create_id());
+                }
+            }
+        }
+        tracker
+    }
+
+    pub(crate) fn get_ids(&self) -> TileIds<W, H> {
+        self.ids
+    }
+
+    /// Applies the steps of a move and the spawned tiles to the ids.
+    pub(crate) fn track(
+        &mut self,
+        cell_events: Vec<CellEvent>,
+        spawned: &[SpawnedTile],
+    ) -> Vec<TileEvent> {
+        let mut events: Vec<TileEvent> = Vec::new();
+        for cell_event in cell_events {
+            match cell_event {
+                CellEvent::Moved { from, to } => {
+                    if let Some(id) = self.take(from) {
+                        self.ids[to.0][to.1] = Some(id);
+                        events.push(TileEvent::TileMoved { id, from, to });
+                    }
+                }
+                CellEvent::Merged {
+                    sources: [first, second],
+                    into,
+                    value,
+                } => {
+                    if let (Some(first_id), Some(second_id)) = (self.take(first), self.take(second))
+                    {
+                        self.ids[into.0][into.1] = Some(first_id);
+                        events.push(TileEvent::TilesMerged {
+                            ids: [first_id, second_id],
+                            into,
+                            value: value.value(),
+                        });
+                    }
+                }
+            }
+        }
+        for SpawnedTile { cell, value } in spawned.iter().copied() {
+            let id = self.create_id();
+            self.ids[cell.0][cell.1] = Some(id);
+            events.push(TileEvent::TileSpawned {
+                id,
+                at: cell,
+                value,
+            });
+        }
+        events
+    }
+
+    fn take(&mut self, (row, column): Coordinate) -> Option<TileId> {
+        self.ids[row][column].take()
+    }
+
+    fn create_id(&mut self) -> TileId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::Direction;
+    use crate::field::tests::*;
+    use crate::field_move_and_merge::*;
+    use crate::tile_tracking::*;
+
+    #[test]
+    fn it_should_assign_ids_in_row_major_order() {
+        let tracker =
+            TileTracker::new([[TWO, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, FOUR]]);
+
+        let ids = tracker.get_ids();
+
+        assert_eq!(ids[0][0], Some(0));
+        assert_eq!(ids[3][3], Some(1));
+        assert_eq!(ids[1][1], None);
+    }
+
+    #[test]
+    fn it_should_keep_the_id_of_a_moved_tile() {
+        let field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]];
+        let mut tracker = TileTracker::new(field);
+
+        let events = tracker.track(move_and_merge_traced(field, Direction::Left, false).2, &[]);
+
+        assert_eq!(
+            events,
+            vec![TileEvent::TileMoved {
+                id: 0,
+                from: (3, 3),
+                to: (3, 0)
+            }]
+        );
+        assert_eq!(tracker.get_ids()[3][0], Some(0));
+        assert_eq!(tracker.get_ids()[3][3], None);
+    }
+
+    #[test]
+    fn it_should_keep_the_first_id_of_merged_tiles() {
+        let field = [[X, X, X, X], [X, X, X, X], [TWO, X, X, X], [TWO, X, X, X]];
+        let mut tracker = TileTracker::new(field);
+
+        let events = tracker.track(move_and_merge_traced(field, Direction::Down, false).2, &[]);
+
+        assert_eq!(
+            events,
+            vec![TileEvent::TilesMerged {
+                ids: [1, 0],
+                into: (3, 0),
+                value: 4
+            }]
+        );
+        assert_eq!(tracker.get_ids()[3][0], Some(1));
+        assert_eq!(tracker.get_ids()[2][0], None);
+    }
+
+    #[test]
+    fn it_should_assign_a_new_id_to_a_spawned_tile() {
+        let field = [[X, X, X, X], [X, X, X, X], [X, X, X, X], [X, X, X, TWO]];
+        let mut tracker = TileTracker::new(field);
+
+        let events = tracker.track(
+            move_and_merge_traced(field, Direction::Up, false).2,
+            &[SpawnedTile {
+                cell: (2, 1),
+                value: 2,
+            }],
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                TileEvent::TileMoved {
+                    id: 0,
+                    from: (3, 3),
+                    to: (0, 3)
+                },
+                TileEvent::TileSpawned {
+                    id: 1,
+                    at: (2, 1),
+                    value: 2
+                }
+            ]
+        );
+    }
+}